@@ -1,7 +1,8 @@
 #!/bin/bash
 set -e
 
-wasm-pack build --release --target nodejs --out-dir temp-pkg --scope axiom-crypto --no-default-features
+# Published packages include the EVM transcript, see the `evm` feature in readme.md
+wasm-pack build --release --target nodejs --out-dir temp-pkg --scope axiom-crypto --no-default-features --features prover,evm
 # manually change pkg/package.json name "@axiom-crypto/halo2-wasm" and version number
 # https://github.com/AleoHQ/sdk/pull/708
 rm temp-pkg/.gitignore
@@ -0,0 +1,11 @@
+#!/bin/bash
+set -e
+
+# Only `Halo2Verifier`, without the circuit builder, keygen and prover, but with the EVM transcript
+wasm-pack build --release --target web --out-dir temp-pkg --scope axiom-crypto --no-default-features --features evm --out-name halo2_wasm_verifier
+rm temp-pkg/.gitignore
+
+# Copy a modified version of package.json and readme.md to pkg/
+node ./scripts/makeSubdirPkg.js halo2_wasm_verifier
+
+mv temp-pkg pkg/verifier
@@ -1,7 +1,8 @@
 #!/bin/bash
 set -e
 
-wasm-pack build --release --target web --out-dir temp-pkg --scope axiom-crypto
+# Published packages include the EVM transcript, see the `evm` feature in readme.md
+wasm-pack build --release --target web --out-dir temp-pkg --scope axiom-crypto --features evm
 # manually change pkg/package.json name "@axiom-crypto/halo2-wasm" and version number
 # https://github.com/AleoHQ/sdk/pull/708
 rm temp-pkg/.gitignore
@@ -5,6 +5,7 @@ rm -rf pkg
 mkdir pkg
 ./scripts/build-js.sh
 ./scripts/build-web.sh
+./scripts/build-verifier.sh
 node ./scripts/makePkg.js
 
 npx tsc
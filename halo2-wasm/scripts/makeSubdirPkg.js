@@ -4,10 +4,24 @@ const fs = require('fs');
 const packageJson = require('../package.json');
 const subdirPackageJson = require('../subdirPackage.json');
 
+// wasm-pack `--out-name` of builds without an `index.js` wrapper, which then export the
+// generated bindings directly
+const outName = process.argv[2];
+
 // Copies a modified version of package.json to the /dist folder
 function copyVersion() {
   let subdirPackageJsonCopy = { ...subdirPackageJson };
   subdirPackageJsonCopy.version = packageJson.version;
+  if (outName) {
+    subdirPackageJsonCopy.module = `${outName}.js`;
+    subdirPackageJsonCopy.types = `${outName}.d.ts`;
+    subdirPackageJsonCopy.files = [
+      `${outName}_bg.wasm`,
+      `${outName}_bg.wasm.d.ts`,
+      `${outName}.d.ts`,
+      `${outName}.js`,
+    ];
+  }
   fs.writeFileSync('./temp-pkg/package.json', JSON.stringify(subdirPackageJsonCopy, null, 2));
 }
 
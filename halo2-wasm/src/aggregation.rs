@@ -0,0 +1,367 @@
+use std::io::BufReader;
+
+use halo2_base::gates::{circuit::CircuitBuilderStage, flex_gate::MultiPhaseThreadBreakPoints};
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+    SerdeFormat,
+};
+use serde::{Deserialize, Serialize};
+use snark_verifier_sdk::{
+    halo2::aggregation::{AggregationCircuit, AggregationConfigParams, VerifierUniversality},
+    Snark, SHPLONK,
+};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    snark::{load_snark, snark_to_bytes},
+    transcript::{gen_snark, TranscriptType},
+    utils::{encode_fr, fr_to_hex, FieldEncoding},
+    verifier::verify_shplonk,
+    Halo2WasmError, MINIMUM_ROWS,
+};
+
+#[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct AggregationConfig {
+    k: usize,
+    lookup_bits: usize,
+    /// Also expose the instances of the aggregated snarks after the accumulator, defaults to false.
+    #[tsify(optional)]
+    #[serde(default)]
+    expose_previous_instances: bool,
+}
+
+/// Aggregates snarks created by `Halo2Wasm.proveSnark` into a single proof.
+///
+/// Follows the same lifecycle as [`crate::Halo2Wasm`]: `config`, `addSnark`, `loadParams`,
+/// `genVk`, `genPk`, then `prove` and `verify`. The number of advice, lookup and fixed columns
+/// is calculated from the snarks at keygen.
+///
+/// Keys can be reused across instances by exporting `getConfigParams`, `getBreakPoints`,
+/// `getVk` and `getPk`, then calling `loadConfigParams`, `loadBreakPoints`, `loadVk` and `loadPk`
+/// instead of keygen.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct AggregationWasm {
+    config: Option<AggregationConfig>,
+    snarks: Vec<Snark>,
+    params: Option<ParamsKZG<Bn256>>,
+    /// Full circuit config, calculated at keygen.
+    config_params: Option<AggregationConfigParams>,
+    break_points: Option<MultiPhaseThreadBreakPoints>,
+    /// Circuit from `genVk`, reused by `genPk`.
+    keygen_circuit: Option<AggregationCircuit>,
+    vk: Option<VerifyingKey<G1Affine>>,
+    pk: Option<ProvingKey<G1Affine>>,
+    /// Instances of the last proof.
+    instances: Vec<Vec<Fr>>,
+    transcript: TranscriptType,
+}
+
+#[wasm_bindgen]
+impl AggregationWasm {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `k` and `lookup_bits` of the aggregation circuit, discarding any keys.
+    pub fn config(&mut self, config: AggregationConfig) {
+        self.config = Some(config);
+        self.reset_keys();
+    }
+
+    /// Adds a snark serialized by `Halo2Wasm.proveSnark` to aggregate.
+    ///
+    /// Keys must be regenerated whenever the aggregated snarks change circuits.
+    #[wasm_bindgen(js_name = addSnark)]
+    pub fn add_snark(&mut self, snark: &[u8]) -> Result<(), Halo2WasmError> {
+        self.snarks.push(load_snark(snark)?);
+        Ok(())
+    }
+
+    /// Removes all snarks and the instances of the last proof.
+    ///
+    /// Keys, config params and break points are kept, so snarks of the same circuits can be
+    /// added and proven without keygen. Call `config` to discard them.
+    #[wasm_bindgen(js_name = clearSnarks)]
+    pub fn clear_snarks(&mut self) {
+        self.snarks.clear();
+        self.instances.clear();
+    }
+
+    #[wasm_bindgen(js_name = loadParams)]
+    pub fn load_params(&mut self, params: &[u8]) -> Result<(), Halo2WasmError> {
+        let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params))
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        self.params = Some(params);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = genVk)]
+    pub fn gen_vk(&mut self) -> Result<(), Halo2WasmError> {
+        let config = self.aggregation_config()?;
+        let mut circuit = self.build_circuit(
+            CircuitBuilderStage::Keygen,
+            AggregationConfigParams {
+                degree: config.k as u32,
+                lookup_bits: config.lookup_bits,
+                ..Default::default()
+            },
+        )?;
+        let config_params = circuit.calculate_params(Some(MINIMUM_ROWS));
+        let vk = keygen_vk(self.params()?, &circuit)
+            .map_err(|e| Halo2WasmError::Synthesis(e.to_string()))?;
+        self.config_params = Some(config_params);
+        self.break_points = Some(circuit.break_points());
+        self.keygen_circuit = Some(circuit);
+        self.vk = Some(vk);
+        self.pk = None;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = genPk)]
+    pub fn gen_pk(&mut self) -> Result<(), Halo2WasmError> {
+        let vk = self.vk()?.clone();
+        let circuit = self
+            .keygen_circuit
+            .as_ref()
+            .ok_or(Halo2WasmError::KeygenCircuitMissing)?;
+        let pk = keygen_pk(self.params()?, vk, circuit)
+            .map_err(|e| Halo2WasmError::Synthesis(e.to_string()))?;
+        self.pk = Some(pk);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getVk)]
+    pub fn get_vk(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        Ok(self.vk()?.to_bytes(SerdeFormat::RawBytesUnchecked))
+    }
+
+    #[wasm_bindgen(js_name = getPk)]
+    pub fn get_pk(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        Ok(self.pk()?.to_bytes(SerdeFormat::RawBytesUnchecked))
+    }
+
+    /// Returns the full circuit config calculated during `genVk`, which `loadVk` and `loadPk`
+    /// need to read the keys.
+    #[wasm_bindgen(js_name = getConfigParams)]
+    pub fn get_config_params(&self) -> Result<JsValue, Halo2WasmError> {
+        serde_wasm_bindgen::to_value(&self.config_params()?)
+            .map_err(|e| Halo2WasmError::Serialize(e.to_string()))
+    }
+
+    /// Sets the full circuit config, as returned by `getConfigParams`, discarding any keys and
+    /// break points. Call it before `loadBreakPoints`, `loadVk` and `loadPk`.
+    #[wasm_bindgen(js_name = loadConfigParams)]
+    pub fn load_config_params(&mut self, config_params: JsValue) -> Result<(), Halo2WasmError> {
+        self.set_config_params(
+            serde_wasm_bindgen::from_value(config_params)
+                .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?,
+        );
+        Ok(())
+    }
+
+    /// Returns the thread break points computed during `genVk`.
+    #[wasm_bindgen(js_name = getBreakPoints)]
+    pub fn get_break_points(&self) -> Result<JsValue, Halo2WasmError> {
+        serde_wasm_bindgen::to_value(self.break_points()?)
+            .map_err(|e| Halo2WasmError::Serialize(e.to_string()))
+    }
+
+    /// Sets the thread break points from keygen, as returned by `getBreakPoints`.
+    #[wasm_bindgen(js_name = loadBreakPoints)]
+    pub fn load_break_points(&mut self, break_points: JsValue) -> Result<(), Halo2WasmError> {
+        self.set_break_points(
+            serde_wasm_bindgen::from_value(break_points)
+                .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?,
+        );
+        Ok(())
+    }
+
+    /// Loads a verifying key from `getVk`, discarding the proving key. Needs the config params
+    /// it was generated with.
+    #[wasm_bindgen(js_name = loadVk)]
+    pub fn load_vk(&mut self, vk: &[u8]) -> Result<(), Halo2WasmError> {
+        let vk = VerifyingKey::<G1Affine>::read::<_, AggregationCircuit>(
+            &mut BufReader::new(vk),
+            SerdeFormat::RawBytesUnchecked,
+            self.config_params()?,
+        )
+        .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        self.keygen_circuit = None;
+        self.vk = Some(vk);
+        self.pk = None;
+        Ok(())
+    }
+
+    /// Loads a proving key from `getPk`, along with its verifying key. Needs the config params
+    /// it was generated with.
+    #[wasm_bindgen(js_name = loadPk)]
+    pub fn load_pk(&mut self, pk: &[u8]) -> Result<(), Halo2WasmError> {
+        let pk = ProvingKey::<G1Affine>::read::<_, AggregationCircuit>(
+            &mut BufReader::new(pk),
+            SerdeFormat::RawBytesUnchecked,
+            self.config_params()?,
+        )
+        .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        self.keygen_circuit = None;
+        self.vk = Some(pk.get_vk().clone());
+        self.pk = Some(pk);
+        Ok(())
+    }
+
+    /// Returns the instances of the last proof as `0x`-prefixed hex strings, one array per
+    /// instance column. The first 12 values are the limbs of the accumulator.
+    #[wasm_bindgen(js_name = getInstanceValues)]
+    pub fn get_instance_values(&self) -> Result<JsValue, Halo2WasmError> {
+        let values: Vec<Vec<String>> = self
+            .instances
+            .iter()
+            .map(|col| col.iter().map(fr_to_hex).collect())
+            .collect();
+        serde_wasm_bindgen::to_value(&values).map_err(|e| Halo2WasmError::Serialize(e.to_string()))
+    }
+
+    /// Same as `getInstanceValues`, with the given encoding.
+    #[wasm_bindgen(js_name = getInstanceValuesEncoded)]
+    pub fn get_instance_values_encoded(&self, encoding: FieldEncoding) -> js_sys::Array {
+        self.instances
+            .iter()
+            .map(|col| {
+                col.iter()
+                    .map(|x| JsValue::from(encode_fr(x, encoding)))
+                    .collect::<js_sys::Array>()
+            })
+            .collect()
+    }
+
+    /// Sets the transcript used by `prove` and `verify`, defaults to Poseidon.
+    ///
+    /// Use the EVM transcript for proofs verified on chain. Only Poseidon snarks can be
+    /// aggregated again.
+    #[wasm_bindgen(js_name = setTranscript)]
+    pub fn set_transcript(&mut self, transcript: TranscriptType) {
+        self.transcript = transcript;
+    }
+
+    pub fn prove(&mut self) -> Result<Vec<u8>, Halo2WasmError> {
+        Ok(self.gen_snark()?.proof)
+    }
+
+    /// Proves the aggregation circuit and returns the serialized snark, so it can be aggregated
+    /// again.
+    #[wasm_bindgen(js_name = proveSnark)]
+    pub fn prove_snark(&mut self) -> Result<Vec<u8>, Halo2WasmError> {
+        snark_to_bytes(self.gen_snark()?, self.transcript)
+    }
+
+    /// Verifies `proof` against the instances of the last proof.
+    pub fn verify(&self, proof: &[u8]) -> Result<(), Halo2WasmError> {
+        verify_shplonk(
+            self.params()?,
+            self.vk()?,
+            proof,
+            &self.instances,
+            self.transcript,
+        )?;
+        Ok(())
+    }
+}
+
+impl AggregationWasm {
+    pub fn snarks(&self) -> &[Snark] {
+        &self.snarks
+    }
+
+    /// Instances of the last proof, one vector per instance column.
+    pub fn instances(&self) -> &[Vec<Fr>] {
+        &self.instances
+    }
+
+    /// Full circuit config calculated at keygen, or set with [`Self::set_config_params`].
+    pub fn config_params(&self) -> Result<AggregationConfigParams, Halo2WasmError> {
+        self.config_params
+            .ok_or(Halo2WasmError::ConfigParamsMissing)
+    }
+
+    pub fn set_config_params(&mut self, config_params: AggregationConfigParams) {
+        self.reset_keys();
+        self.config_params = Some(config_params);
+    }
+
+    pub fn break_points(&self) -> Result<&MultiPhaseThreadBreakPoints, Halo2WasmError> {
+        self.break_points
+            .as_ref()
+            .ok_or(Halo2WasmError::BreakPointsMissing)
+    }
+
+    pub fn set_break_points(&mut self, break_points: MultiPhaseThreadBreakPoints) {
+        self.break_points = Some(break_points);
+    }
+
+    fn gen_snark(&mut self) -> Result<Snark, Halo2WasmError> {
+        let config_params = self.config_params()?;
+        let break_points = self
+            .break_points
+            .clone()
+            .ok_or(Halo2WasmError::BreakPointsMissing)?;
+        let circuit = self
+            .build_circuit(CircuitBuilderStage::Prover, config_params)?
+            .use_break_points(break_points);
+        let snark = gen_snark(self.params()?, self.pk()?, circuit, self.transcript)?;
+        self.instances = snark.instances.clone();
+        Ok(snark)
+    }
+
+    fn build_circuit(
+        &self,
+        stage: CircuitBuilderStage,
+        config_params: AggregationConfigParams,
+    ) -> Result<AggregationCircuit, Halo2WasmError> {
+        let config = self.aggregation_config()?;
+        if self.snarks.is_empty() {
+            return Err(Halo2WasmError::NoSnarks);
+        }
+        let mut circuit = AggregationCircuit::new::<SHPLONK>(
+            stage,
+            config_params,
+            self.params()?,
+            self.snarks.clone(),
+            VerifierUniversality::None,
+        );
+        if config.expose_previous_instances {
+            circuit.expose_previous_instances(false);
+        }
+        Ok(circuit)
+    }
+
+    fn reset_keys(&mut self) {
+        self.config_params = None;
+        self.break_points = None;
+        self.keygen_circuit = None;
+        self.vk = None;
+        self.pk = None;
+        self.instances.clear();
+    }
+
+    fn aggregation_config(&self) -> Result<&AggregationConfig, Halo2WasmError> {
+        self.config.as_ref().ok_or(Halo2WasmError::NotConfigured)
+    }
+
+    fn params(&self) -> Result<&ParamsKZG<Bn256>, Halo2WasmError> {
+        self.params.as_ref().ok_or(Halo2WasmError::ParamsMissing)
+    }
+
+    fn vk(&self) -> Result<&VerifyingKey<G1Affine>, Halo2WasmError> {
+        self.vk.as_ref().ok_or(Halo2WasmError::VkMissing)
+    }
+
+    fn pk(&self) -> Result<&ProvingKey<G1Affine>, Halo2WasmError> {
+        self.pk.as_ref().ok_or(Halo2WasmError::PkMissing)
+    }
+}
@@ -0,0 +1,37 @@
+use halo2_base::gates::{circuit::BaseCircuitParams, flex_gate::MultiPhaseThreadBreakPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::{from_versioned_bytes, to_versioned_bytes},
+    Halo2WasmError,
+};
+
+/// Magic bytes prefixing every serialized [`CircuitArtifact`].
+pub const ARTIFACT_MAGIC: [u8; 4] = *b"H2WA";
+/// Current version of the [`CircuitArtifact`] format.
+pub const ARTIFACT_VERSION: u32 = 1;
+
+/// Everything needed to reconstruct a circuit's keys without knowing its configuration up front.
+///
+/// Serialized as `ARTIFACT_MAGIC || ARTIFACT_VERSION (u32 LE) || bincode(CircuitArtifact)`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircuitArtifact {
+    pub circuit_params: BaseCircuitParams,
+    pub break_points: MultiPhaseThreadBreakPoints,
+    /// Instance column of each virtual instance.
+    pub instance_columns: Vec<usize>,
+    /// Verifying key in `SerdeFormat::RawBytesUnchecked`.
+    pub vk: Vec<u8>,
+    /// Proving key in `SerdeFormat::RawBytesUnchecked`, if bundled.
+    pub pk: Option<Vec<u8>>,
+}
+
+impl CircuitArtifact {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        to_versioned_bytes(ARTIFACT_MAGIC, ARTIFACT_VERSION, self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Halo2WasmError> {
+        from_versioned_bytes(ARTIFACT_MAGIC, ARTIFACT_VERSION, "circuit artifact", bytes)
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use wasm_bindgen::{JsError, JsValue};
+
+use crate::transcript::TranscriptType;
+
+/// Errors surfaced by [`crate::Halo2Wasm`].
+///
+/// Exported methods return these directly, wasm-bindgen throws them as a JS `Error` so callers
+/// can catch them instead of the wasm instance trapping on a panic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Halo2WasmError {
+    /// `config` has not been called yet.
+    NotConfigured,
+    /// The circuit was configured without lookup bits, which range checks need.
+    LookupBitsMissing,
+    /// KZG params have not been loaded.
+    ParamsMissing,
+    /// No verifying key has been generated or loaded.
+    VkMissing,
+    /// No proving key has been generated or loaded.
+    PkMissing,
+    /// The aggregation circuit config is unknown because keys were neither generated nor loaded.
+    ConfigParamsMissing,
+    /// The circuit from `genVk` is missing, so a proving key cannot be generated.
+    KeygenCircuitMissing,
+    /// Failed to deserialize params, keys or other inputs.
+    Deserialize(String),
+    /// Failed to serialize keys or other outputs.
+    Serialize(String),
+    /// Circuit synthesis failed during keygen or proving.
+    Synthesis(String),
+    /// Proof verification failed.
+    VerifyFailed(String),
+    /// The mock prover found unsatisfied constraints.
+    MockFailed(String),
+    /// The virtual instance column `col` does not exist.
+    InstanceColumnOutOfRange { col: usize, num_columns: usize },
+    /// The cell `offset` has not been assigned in the current context.
+    CellOutOfRange { offset: usize, num_cells: usize },
+    /// The number of instance columns does not match the verifying key.
+    InstanceColumnMismatch { expected: usize, found: usize },
+    /// A string could not be parsed as a field element.
+    InvalidFieldElement(String),
+    /// A string argument, such as a bit size, could not be parsed as an integer.
+    InvalidInteger(String),
+    /// Thread break points are unknown because keys were neither generated nor imported.
+    BreakPointsMissing,
+    /// The circuit is in witness generation only mode, so it cannot be used for keygen or mocking.
+    WitnessGenOnly,
+    /// No config in the requested range fits the circuit.
+    AutoConfigFailed(String),
+    /// Virtual instances are not mapped onto valid instance columns.
+    InvalidInstanceColumns(String),
+    /// No snarks have been added to aggregate.
+    NoSnarks,
+    /// The thread does not exist.
+    ThreadOutOfRange { thread: usize, num_threads: usize },
+    /// All value handles of the circuit are taken.
+    TooManyCells(usize),
+    /// A batch of operations is malformed.
+    InvalidBatch(String),
+    /// A value handle belongs to another circuit, or to this one before it was cleared.
+    StaleHandle(usize),
+    /// A Merkle path has a different number of siblings and path bits.
+    MerklePathMismatch { siblings: usize, path_bits: usize },
+    /// A Merkle tree has no leaves.
+    NoLeaves,
+    /// The leaf index is not in the Merkle tree.
+    LeafOutOfRange { index: usize, num_leaves: usize },
+    /// The Solidity verifier could not be generated from the verifying key.
+    SolidityCodegen(String),
+    /// The EVM transcript or calldata was requested from a wasm build without the `evm` feature.
+    EvmUnsupported,
+    /// A proof was created with a different transcript than required.
+    TranscriptMismatch {
+        expected: TranscriptType,
+        found: TranscriptType,
+    },
+}
+
+impl fmt::Display for Halo2WasmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConfigured => write!(f, "circuit is not configured, call `config` first"),
+            Self::LookupBitsMissing => write!(f, "circuit is configured without lookup bits"),
+            Self::ParamsMissing => write!(f, "params are not loaded, call `loadParams` first"),
+            Self::VkMissing => write!(
+                f,
+                "verifying key is missing, call `genVk` or `loadVk` first"
+            ),
+            Self::PkMissing => write!(f, "proving key is missing, call `genPk` or `loadPk` first"),
+            Self::ConfigParamsMissing => write!(
+                f,
+                "config params are missing, call `genVk` or `loadConfigParams` first"
+            ),
+            Self::KeygenCircuitMissing => {
+                write!(f, "keygen circuit is missing, call `genVk` before `genPk`")
+            }
+            Self::Deserialize(e) => write!(f, "deserialization failed: {e}"),
+            Self::Serialize(e) => write!(f, "serialization failed: {e}"),
+            Self::Synthesis(e) => write!(f, "circuit synthesis failed: {e}"),
+            Self::VerifyFailed(e) => write!(f, "proof verification failed: {e}"),
+            Self::MockFailed(e) => write!(f, "mock prover failed: {e}"),
+            Self::InstanceColumnOutOfRange { col, num_columns } => write!(
+                f,
+                "instance column {col} out of range, circuit has {num_columns} instance columns"
+            ),
+            Self::CellOutOfRange { offset, num_cells } => write!(
+                f,
+                "cell offset {offset} out of range, context has {num_cells} assigned cells"
+            ),
+            Self::InstanceColumnMismatch { expected, found } => write!(
+                f,
+                "expected instances for {expected} instance columns, found {found}"
+            ),
+            Self::InvalidFieldElement(e) => write!(f, "invalid field element: {e}"),
+            Self::InvalidInteger(s) => write!(f, "`{s}` is not a valid integer"),
+            Self::BreakPointsMissing => write!(
+                f,
+                "break points are missing, call `genVk` or `importCircuitArtifact` first"
+            ),
+            Self::WitnessGenOnly => write!(
+                f,
+                "circuit is in witness generation only mode, call `config` to reset it"
+            ),
+            Self::AutoConfigFailed(e) => write!(f, "auto config failed: {e}"),
+            Self::InvalidInstanceColumns(e) => write!(f, "invalid instance columns: {e}"),
+            Self::NoSnarks => write!(f, "no snarks to aggregate, call `addSnark` first"),
+            Self::ThreadOutOfRange {
+                thread,
+                num_threads,
+            } => write!(
+                f,
+                "thread {thread} does not exist, the circuit has {num_threads} threads"
+            ),
+            Self::TooManyCells(max) => write!(
+                f,
+                "all {max} value handles of the circuit are taken, clear it to reuse them"
+            ),
+            Self::InvalidBatch(e) => write!(f, "invalid batch: {e}"),
+            Self::StaleHandle(handle) => write!(
+                f,
+                "value {handle:#x} belongs to another circuit or was created before it was cleared"
+            ),
+            Self::MerklePathMismatch {
+                siblings,
+                path_bits,
+            } => write!(
+                f,
+                "Merkle path has {siblings} siblings but {path_bits} path bits"
+            ),
+            Self::NoLeaves => write!(f, "a Merkle tree needs at least one leaf"),
+            Self::LeafOutOfRange { index, num_leaves } => write!(
+                f,
+                "leaf {index} is out of range, the tree has {num_leaves} leaves"
+            ),
+            Self::SolidityCodegen(e) => write!(f, "Solidity verifier generation failed: {e}"),
+            Self::EvmUnsupported => write!(
+                f,
+                "EVM transcript is not supported, build halo2-wasm with the `evm` feature"
+            ),
+            Self::TranscriptMismatch { expected, found } => write!(
+                f,
+                "expected a proof with a {expected:?} transcript, found {found:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Halo2WasmError {}
+
+impl From<Halo2WasmError> for JsValue {
+    fn from(e: Halo2WasmError) -> Self {
+        JsError::from(e).into()
+    }
+}
@@ -0,0 +1,22 @@
+//! Calldata encoding for Solidity verifiers, and their generation natively and in wasm builds
+//! with the `evm` feature.
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::utils::fr_to_bytes_be;
+
+#[cfg(any(feature = "evm", not(target_family = "wasm")))]
+mod solidity;
+
+#[cfg(any(feature = "evm", not(target_family = "wasm")))]
+pub use solidity::gen_solidity_verifier;
+
+/// Encodes `instances` and `proof` as calldata for a verifier from `gen_solidity_verifier`:
+/// every instance as a 32-byte big-endian word, column by column, followed by the proof bytes.
+pub fn encode_calldata(instances: &[Vec<Fr>], proof: &[u8]) -> Vec<u8> {
+    instances
+        .iter()
+        .flatten()
+        .flat_map(fr_to_bytes_be)
+        .chain(proof.iter().copied())
+        .collect()
+}
@@ -0,0 +1,56 @@
+//! Solidity verifier generation, available natively and in wasm builds with the `evm` feature.
+use std::rc::Rc;
+
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::VerifyingKey,
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use snark_verifier::{
+    loader::evm::EvmLoader,
+    pcs::kzg::KzgDecidingKey,
+    system::halo2::{compile, transcript::evm::EvmTranscript, Config},
+    verifier::{plonk::PlonkVerifier, SnarkVerifier},
+};
+use snark_verifier_sdk::SHPLONK;
+
+use crate::{Halo2Wasm, Halo2WasmError};
+
+/// Generates the source of a Solidity contract verifying SHPLONK proofs for `vk`, created with
+/// the EVM transcript.
+///
+/// `num_instance` is the number of public inputs in each instance column. The contract
+/// expects calldata as produced by [`super::encode_calldata`].
+pub fn gen_solidity_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+) -> Result<String, Halo2WasmError> {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(num_instance.clone()),
+    );
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let instances = transcript.load_instances(num_instance);
+    let proof = PlonkVerifier::<SHPLONK>::read_proof(&dk, &protocol, &instances, &mut transcript)
+        .map_err(|e| Halo2WasmError::SolidityCodegen(format!("{e:?}")))?;
+    PlonkVerifier::<SHPLONK>::verify(&dk, &protocol, &instances, &proof)
+        .map_err(|e| Halo2WasmError::SolidityCodegen(format!("{e:?}")))?;
+    Ok(loader.solidity_code())
+}
+
+impl Halo2Wasm {
+    /// Generates a Solidity verifier for the loaded params and verifying key, with the
+    /// current number of public inputs in each instance column.
+    ///
+    /// Proofs must be created with the EVM transcript, see `setTranscript`.
+    pub fn gen_solidity_verifier(&self) -> Result<String, Halo2WasmError> {
+        let num_instance = self.instance_cells().iter().map(Vec::len).collect();
+        gen_solidity_verifier(self.params()?, self.vk()?, num_instance)
+    }
+}
@@ -1,14 +1,13 @@
+use std::cell::{RefCell, RefMut};
 use std::rc::Rc;
-use std::{cell::RefCell, str::FromStr};
 
 use halo2_base::{
     gates::{
         circuit::builder::BaseCircuitBuilder,
-        flex_gate::{GateChip, GateInstructions},
+        flex_gate::{threads::parallelize_core, GateChip, GateInstructions},
         range::{RangeChip, RangeInstructions},
     },
-    halo2_proofs::{arithmetic::CurveAffine, halo2curves::ff::PrimeField},
-    poseidon::hasher::{spec::OptimizedPoseidonSpec, PoseidonHasher},
+    halo2_proofs::arithmetic::CurveAffine,
     utils::{biguint_to_fe, fe_to_biguint, modulus},
     AssignedValue, Context,
     QuantumCell::{Constant, Existing},
@@ -19,9 +18,27 @@ use num_integer::Integer;
 use num_traits::One;
 use wasm_bindgen::prelude::*;
 
-use crate::Halo2Wasm;
+use crate::{
+    mock::MockCheckResult,
+    utils::{
+        decode_js_fr, encode_fr, fr_from_bytes_be, fr_from_str, fr_to_hex, parse_int, FieldEncoding,
+    },
+    Halo2Wasm, Halo2WasmError,
+};
 
+pub mod batch;
+mod bits;
+mod debug;
 pub mod ecc;
+pub(crate) mod handles;
+pub mod keccak;
+pub mod merkle;
+pub mod poseidon;
+pub mod sha256;
+
+use debug::OpTrace;
+use handles::Handles;
+use poseidon::PoseidonHashers;
 
 pub const T: usize = 3;
 pub const RATE: usize = 2;
@@ -37,298 +54,482 @@ pub struct Halo2LibWasm {
     gate: GateChip<Fr>,
     range: RangeChip<Fr>,
     builder: Rc<RefCell<BaseCircuitBuilder<Fr>>>,
+    /// Handles of the cells of the circuit, forgotten whenever it is cleared.
+    handles: Rc<RefCell<Handles>>,
+    /// Thread all ops are assigned in with the handle generation it was selected in, `None` for
+    /// the last one. `BaseCircuitBuilder` only assigns the first phase, so all threads belong to
+    /// it. Threads selected before the circuit was last cleared are ignored.
+    thread: Option<(u64, usize)>,
+    /// Provenance of assigned offsets, only recorded in debug mode.
+    trace: RefCell<Option<OpTrace>>,
+    /// Poseidon hashers with their constants assigned in the circuit.
+    poseidon: RefCell<PoseidonHashers>,
 }
 
 #[wasm_bindgen]
 impl Halo2LibWasm {
     #[wasm_bindgen(constructor)]
-    pub fn new(circuit: &Halo2Wasm) -> Self {
+    pub fn new(circuit: &Halo2Wasm) -> Result<Halo2LibWasm, Halo2WasmError> {
         let gate = GateChip::new();
-        let lookup_bits = circuit.circuit_params.clone().unwrap().lookup_bits.unwrap();
+        let lookup_bits = circuit
+            .circuit_params()?
+            .lookup_bits
+            .ok_or(Halo2WasmError::LookupBitsMissing)?;
         let range = RangeChip::new(
             lookup_bits,
             circuit.circuit.borrow().lookup_manager().clone(),
         );
-        Halo2LibWasm {
+        Ok(Halo2LibWasm {
             gate,
             range,
             builder: Rc::clone(&circuit.circuit),
-        }
+            handles: Rc::clone(&circuit.handles),
+            thread: None,
+            trace: RefCell::new(None),
+            poseidon: RefCell::default(),
+        })
     }
 
-    pub fn config(&mut self) {
+    pub fn config(&mut self) -> Result<(), Halo2WasmError> {
         let gate = GateChip::new();
-        let lookup_bits = self.builder.borrow().config_params.lookup_bits.unwrap();
+        let lookup_bits = self
+            .builder
+            .borrow()
+            .config_params
+            .lookup_bits
+            .ok_or(Halo2WasmError::LookupBitsMissing)?;
         let range = RangeChip::new(
             lookup_bits,
             self.builder.borrow_mut().lookup_manager().clone(),
         );
         self.gate = gate;
         self.range = range;
+        self.thread = None;
+        Ok(())
+    }
+
+    /// Opens a new thread and assigns all following ops in it, returning
+    /// its index.
+    ///
+    /// Threads are independent contexts, so a large circuit can be built in separate pieces.
+    /// Values from any thread can be used as inputs, the copy constraints are
+    /// added automatically.
+    #[wasm_bindgen(js_name = newThread)]
+    pub fn new_thread(&mut self) -> Result<usize, Halo2WasmError> {
+        let thread = self.builder.borrow_mut().new_thread(0).id();
+        self.select_thread(thread);
+        Ok(thread)
+    }
+
+    /// Switches to an existing thread.
+    #[wasm_bindgen(js_name = setThread)]
+    pub fn set_thread(&mut self, thread: usize) -> Result<(), Halo2WasmError> {
+        let num_threads = self.num_threads();
+        if thread >= num_threads {
+            return Err(Halo2WasmError::ThreadOutOfRange {
+                thread,
+                num_threads,
+            });
+        }
+        self.select_thread(thread);
+        Ok(())
     }
 
-    fn get_assigned_value(&self, idx: usize) -> AssignedValue<Fr> {
-        self.builder.borrow().core().phase_manager[0]
-            .threads
-            .last()
-            .unwrap()
-            .get(idx as isize)
+    /// Index of the thread ops are assigned in.
+    pub fn thread(&self) -> usize {
+        self.selected_thread()
+            .unwrap_or_else(|| self.num_threads().saturating_sub(1))
     }
 
-    fn get_assigned_values(&self, a: &[u32]) -> Vec<AssignedValue<Fr>> {
-        a.iter()
-            .map(|x| self.get_assigned_value(*x as usize))
-            .collect()
+    /// Number of threads.
+    #[wasm_bindgen(js_name = numThreads)]
+    pub fn num_threads(&self) -> usize {
+        self.builder.borrow().core().phase_manager[0].threads.len()
     }
 
-    fn to_js_assigned_value(&self, a: AssignedValue<Fr>) -> usize {
-        a.cell.unwrap().offset
+    /// Enables debug mode, recording the operation and label that assigned each offset from now on.
+    #[wasm_bindgen(js_name = enableDebug)]
+    pub fn enable_debug(&mut self) {
+        self.trace.get_mut().get_or_insert_with(OpTrace::default);
     }
 
-    fn to_js_assigned_values(&self, a: Vec<AssignedValue<Fr>>) -> Vec<u32> {
-        let idxs: Vec<u32> = a.iter().map(|x| x.cell.unwrap().offset as u32).collect();
-        idxs
+    /// Sets the label, e.g. a JS call site like `circuit.ts:42`, attached to all following
+    /// operations in debug mode. Pass `undefined` to clear it.
+    #[wasm_bindgen(js_name = setDebugLabel)]
+    pub fn set_debug_label(&mut self, label: Option<String>) {
+        if let Some(trace) = self.trace.get_mut() {
+            trace.set_label(label);
+        }
     }
 
-    pub fn add(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.add(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    /// Describes the operation that assigned the value `a` in debug mode,
+    /// e.g. `range_check at circuit.ts:42`.
+    #[wasm_bindgen(js_name = debugOp)]
+    pub fn debug_op(&self, a: usize) -> Option<String> {
+        let (thread, offset) = self.handles.borrow().cell(a).ok()?;
+        self.debug_op_at(thread, offset)
     }
 
-    pub fn sub(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.sub(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    /// Attaches the operation that assigned each cell of the failures in `result` in debug mode.
+    #[wasm_bindgen(js_name = annotateMockCheck)]
+    pub fn annotate_mock_check(&self, mut result: MockCheckResult) -> MockCheckResult {
+        for cell in result
+            .failures
+            .iter_mut()
+            .flat_map(|failure| failure.cells.iter_mut())
+        {
+            cell.source = self.debug_op_at(cell.thread, cell.offset);
+        }
+        result
     }
 
-    pub fn neg(&mut self, a: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let out = self.gate.neg(self.builder.borrow_mut().main(0), a);
-        self.to_js_assigned_value(out)
+    fn debug_op_at(&self, thread: usize, offset: usize) -> Option<String> {
+        let trace = self.trace.borrow();
+        let generation = self.handles.borrow().generation();
+        let op = trace.as_ref()?.find(generation, thread, offset)?;
+        Some(op.to_string())
     }
 
-    pub fn mul(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.mul(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    /// Thread and offset the next op starts assigning at, `None` unless in debug mode.
+    fn trace_start(&self) -> Option<(usize, usize)> {
+        if self.trace.borrow().is_none() {
+            return None;
+        }
+        let ctx = self.ctx();
+        Some((ctx.id(), ctx.advice.len()))
+    }
+
+    /// Records `op` as assigning the offsets from `start` in debug mode once it has succeeded, so
+    /// ops rejecting their inputs never show up.
+    fn trace_op<T>(
+        &self,
+        op: &'static str,
+        start: Option<(usize, usize)>,
+        out: Result<T, Halo2WasmError>,
+    ) -> Result<T, Halo2WasmError> {
+        if out.is_ok() {
+            self.record_op(op, start);
+        }
+        out
     }
 
-    pub fn mul_add(&mut self, a: usize, b: usize, c: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let c = self.get_assigned_value(c);
-        let out = self
-            .gate
-            .mul_add(self.builder.borrow_mut().main(0), a, b, c);
-        self.to_js_assigned_value(out)
+    fn record_op(&self, op: &'static str, start: Option<(usize, usize)>) {
+        if let (Some(trace), Some((thread, start))) = (self.trace.borrow_mut().as_mut(), start) {
+            trace.record(self.handles.borrow().generation(), thread, op, start);
+        }
     }
 
-    pub fn mul_not(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.mul_not(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    fn select_thread(&mut self, thread: usize) {
+        self.thread = Some((self.handles.borrow().generation(), thread));
     }
 
-    pub fn assert_bit(&mut self, a: usize) {
-        let a = self.get_assigned_value(a);
-        self.gate.assert_bit(self.builder.borrow_mut().main(0), a);
+    /// The thread selected with `setThread`, `newThread` or `parallelize` since the circuit was
+    /// last cleared.
+    fn selected_thread(&self) -> Option<usize> {
+        let generation = self.handles.borrow().generation();
+        self.thread
+            .filter(|&(selected, _)| selected == generation)
+            .map(|(_, thread)| thread)
     }
 
-    pub fn div_unsafe(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self
-            .gate
-            .div_unsafe(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    /// The context of the current thread, creating the first thread if needed.
+    fn ctx(&self) -> RefMut<'_, Context<Fr>> {
+        let thread = self.selected_thread();
+        RefMut::map(self.builder.borrow_mut(), |builder| match thread {
+            Some(thread) => &mut builder.pool(0).threads[thread],
+            None => builder.main(0),
+        })
     }
 
-    pub fn assert_is_const(&mut self, a: usize, b: &str) {
-        let a = self.get_assigned_value(a);
-        let x = Fr::from_str_vartime(b).unwrap();
-        self.gate
-            .assert_is_const(self.builder.borrow_mut().main(0), &a, &x);
+    fn is_assigned(&self, handle: usize) -> bool {
+        self.assigned_value(handle).is_ok()
     }
 
-    pub fn inner_product(&mut self, a: &[u32], b: &[u32]) -> usize {
-        let a = self.get_assigned_values(a);
-        let b = self.get_assigned_values(b);
-        let out = self.gate.inner_product(
-            self.builder.borrow_mut().main(0),
-            a,
-            b.iter().map(|x| Existing(*x)).collect_vec(),
-        );
-        self.to_js_assigned_value(out)
+    fn get_assigned_values(&self, a: &[u32]) -> Result<Vec<AssignedValue<Fr>>, Halo2WasmError> {
+        a.iter().map(|x| self.assigned_value(*x as usize)).collect()
     }
 
-    pub fn sum(&mut self, a: &[u32]) -> usize {
-        let a = self.get_assigned_values(a);
-        let out = self.gate.sum(self.builder.borrow_mut().main(0), a);
-        self.to_js_assigned_value(out)
+    fn load_witness(&self, x: Fr) -> Result<usize, Halo2WasmError> {
+        let witness = self.ctx().load_witness(x);
+        self.to_js_assigned_value(witness)
     }
 
-    pub fn and(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.and(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    fn load_constant(&self, x: Fr) -> Result<usize, Halo2WasmError> {
+        let constant = self.ctx().load_constant(x);
+        self.to_js_assigned_value(constant)
     }
 
-    pub fn or(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.or(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
+    fn to_js_assigned_values(&self, a: Vec<AssignedValue<Fr>>) -> Result<Vec<u32>, Halo2WasmError> {
+        a.into_iter()
+            .map(|x| Ok(self.to_js_assigned_value(x)? as u32))
+            .collect()
     }
 
-    pub fn not(&mut self, a: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let out = self.gate.not(self.builder.borrow_mut().main(0), a);
-        self.to_js_assigned_value(out)
+    pub fn add(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.add(&mut self.ctx(), a, b);
+        self.trace_op("add", start, self.to_js_assigned_value(out))
     }
 
-    pub fn dec(&mut self, a: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let out = self.gate.dec(self.builder.borrow_mut().main(0), a);
-        self.to_js_assigned_value(out)
+    pub fn sub(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.sub(&mut self.ctx(), a, b);
+        self.trace_op("sub", start, self.to_js_assigned_value(out))
     }
 
-    pub fn select(&mut self, a: usize, b: usize, sel: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let sel = self.get_assigned_value(sel);
-        let out = self
-            .gate
-            .select(self.builder.borrow_mut().main(0), a, b, sel);
-        self.to_js_assigned_value(out)
+    pub fn neg(&mut self, a: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let out = self.gate.neg(&mut self.ctx(), a);
+        self.trace_op("neg", start, self.to_js_assigned_value(out))
     }
 
-    pub fn or_and(&mut self, a: usize, b: usize, c: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let c = self.get_assigned_value(c);
-        let out = self.gate.or_and(self.builder.borrow_mut().main(0), a, b, c);
-        self.to_js_assigned_value(out)
+    pub fn mul(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.mul(&mut self.ctx(), a, b);
+        self.trace_op("mul", start, self.to_js_assigned_value(out))
     }
 
-    pub fn bits_to_indicator(&mut self, a: &[u32]) -> Vec<u32> {
-        let a = self.get_assigned_values(a);
-        let out = self
-            .gate
-            .bits_to_indicator(self.builder.borrow_mut().main(0), &a);
-        self.to_js_assigned_values(out)
+    pub fn mul_add(&mut self, a: usize, b: usize, c: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let c = self.assigned_value(c)?;
+        let out = self.gate.mul_add(&mut self.ctx(), a, b, c);
+        self.trace_op("mul_add", start, self.to_js_assigned_value(out))
     }
 
-    pub fn idx_to_indicator(&mut self, a: usize, b: &str) -> Vec<u32> {
-        let a = self.get_assigned_value(a);
-        let b: usize = b.parse().unwrap();
-        let out = self
-            .gate
-            .idx_to_indicator(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_values(out)
+    pub fn mul_not(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.mul_not(&mut self.ctx(), a, b);
+        self.trace_op("mul_not", start, self.to_js_assigned_value(out))
     }
 
-    pub fn select_by_indicator(&mut self, a: &[u32], indicator: &[u32]) -> usize {
-        let a = self.get_assigned_values(a);
-        let indicator = self.get_assigned_values(indicator);
-        let out = self
-            .gate
-            .select_by_indicator(self.builder.borrow_mut().main(0), a, indicator);
-        self.to_js_assigned_value(out)
+    pub fn assert_bit(&mut self, a: usize) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        self.gate.assert_bit(&mut self.ctx(), a);
+        self.trace_op("assert_bit", start, Ok(()))
     }
 
-    pub fn select_from_idx(&mut self, a: &[u32], idx: usize) -> usize {
-        let a = self.get_assigned_values(a);
-        let idx = self.get_assigned_value(idx);
-        let out = self
-            .gate
-            .select_from_idx(self.builder.borrow_mut().main(0), a, idx);
-        self.to_js_assigned_value(out)
+    pub fn div_unsafe(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.div_unsafe(&mut self.ctx(), a, b);
+        self.trace_op("div_unsafe", start, self.to_js_assigned_value(out))
     }
 
-    pub fn is_zero(&mut self, a: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let out = self.gate.is_zero(self.builder.borrow_mut().main(0), a);
-        self.to_js_assigned_value(out)
+    /// Constrains `a` to equal the decimal or `0x`-prefixed hex constant `b`.
+    pub fn assert_is_const(&mut self, a: usize, b: &str) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let x = fr_from_str(b)?;
+        self.gate.assert_is_const(&mut self.ctx(), &a, &x);
+        self.trace_op("assert_is_const", start, Ok(()))
     }
 
-    pub fn is_equal(&mut self, a: usize, b: usize) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let out = self.gate.is_equal(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
-    }
-
-    pub fn num_to_bits(&mut self, a: usize, num_bits: &str) -> Vec<u32> {
-        let a = self.get_assigned_value(a);
-        let num_bits: usize = num_bits.parse().unwrap();
-        let out = self
-            .gate
-            .num_to_bits(self.builder.borrow_mut().main(0), a, num_bits);
-        self.to_js_assigned_values(out)
+    pub fn inner_product(&mut self, a: &[u32], b: &[u32]) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let b = self.get_assigned_values(b)?;
+        let out = self.gate.inner_product(
+            &mut self.ctx(),
+            a,
+            b.iter().map(|x| Existing(*x)).collect_vec(),
+        );
+        self.trace_op("inner_product", start, self.to_js_assigned_value(out))
     }
 
-    pub fn constrain_equal(&mut self, a: usize, b: usize) {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        self.builder.borrow_mut().main(0).constrain_equal(&a, &b);
+    pub fn sum(&mut self, a: &[u32]) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let out = self.gate.sum(&mut self.ctx(), a);
+        self.trace_op("sum", start, self.to_js_assigned_value(out))
     }
 
-    pub fn range_check(&self, a: usize, b: &str) {
-        let a = self.get_assigned_value(a);
-        let b: usize = b.parse().unwrap();
-        self.range
-            .range_check(self.builder.borrow_mut().main(0), a, b);
+    pub fn and(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.and(&mut self.ctx(), a, b);
+        self.trace_op("and", start, self.to_js_assigned_value(out))
     }
 
-    pub fn check_less_than(&mut self, a: usize, b: usize, size: &str) {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let size: usize = size.parse().unwrap();
-        self.range
-            .check_less_than(self.builder.borrow_mut().main(0), a, b, size);
+    pub fn or(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.or(&mut self.ctx(), a, b);
+        self.trace_op("or", start, self.to_js_assigned_value(out))
     }
 
-    pub fn check_less_than_safe(&mut self, a: usize, b: &str) {
-        let a = self.get_assigned_value(a);
-        let b: u64 = b.parse().unwrap();
-        self.range
-            .check_less_than_safe(self.builder.borrow_mut().main(0), a, b);
+    pub fn not(&mut self, a: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let out = self.gate.not(&mut self.ctx(), a);
+        self.trace_op("not", start, self.to_js_assigned_value(out))
     }
 
-    pub fn is_less_than(&mut self, a: usize, b: usize, size: &str) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let size: usize = size.parse().unwrap();
-        let out = self
-            .range
-            .is_less_than(self.builder.borrow_mut().main(0), a, b, size);
-        self.to_js_assigned_value(out)
+    pub fn dec(&mut self, a: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let out = self.gate.dec(&mut self.ctx(), a);
+        self.trace_op("dec", start, self.to_js_assigned_value(out))
     }
-
-    pub fn is_less_than_safe(&mut self, a: usize, b: &str) -> usize {
-        let a = self.get_assigned_value(a);
-        let b: u64 = b.parse().unwrap();
-        let out = self
-            .range
-            .is_less_than_safe(self.builder.borrow_mut().main(0), a, b);
-        self.to_js_assigned_value(out)
-    }
-
-    pub fn div_mod(&mut self, a: usize, b: &str, size: &str) -> Vec<u32> {
-        let a = self.get_assigned_value(a);
-        let b = BigUint::from_str(b).unwrap();
-        let size: usize = size.parse().unwrap();
-        let out = self
-            .range
-            .div_mod(self.builder.borrow_mut().main(0), a, b, size);
+
+    pub fn select(&mut self, a: usize, b: usize, sel: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let sel = self.assigned_value(sel)?;
+        let out = self.gate.select(&mut self.ctx(), a, b, sel);
+        self.trace_op("select", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn or_and(&mut self, a: usize, b: usize, c: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let c = self.assigned_value(c)?;
+        let out = self.gate.or_and(&mut self.ctx(), a, b, c);
+        self.trace_op("or_and", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn bits_to_indicator(&mut self, a: &[u32]) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let out = self.gate.bits_to_indicator(&mut self.ctx(), &a);
+        self.trace_op("bits_to_indicator", start, self.to_js_assigned_values(out))
+    }
+
+    pub fn idx_to_indicator(&mut self, a: usize, b: &str) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b: usize = parse_int(b)?;
+        let out = self.gate.idx_to_indicator(&mut self.ctx(), a, b);
+        self.trace_op("idx_to_indicator", start, self.to_js_assigned_values(out))
+    }
+
+    pub fn select_by_indicator(
+        &mut self,
+        a: &[u32],
+        indicator: &[u32],
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let indicator = self.get_assigned_values(indicator)?;
+        let out = self.gate.select_by_indicator(&mut self.ctx(), a, indicator);
+        self.trace_op("select_by_indicator", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn select_from_idx(&mut self, a: &[u32], idx: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let idx = self.assigned_value(idx)?;
+        let out = self.gate.select_from_idx(&mut self.ctx(), a, idx);
+        self.trace_op("select_from_idx", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn is_zero(&mut self, a: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let out = self.gate.is_zero(&mut self.ctx(), a);
+        self.trace_op("is_zero", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn is_equal(&mut self, a: usize, b: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let out = self.gate.is_equal(&mut self.ctx(), a, b);
+        self.trace_op("is_equal", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn num_to_bits(&mut self, a: usize, num_bits: &str) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let num_bits: usize = parse_int(num_bits)?;
+        let out = self.gate.num_to_bits(&mut self.ctx(), a, num_bits);
+        self.trace_op("num_to_bits", start, self.to_js_assigned_values(out))
+    }
+
+    pub fn constrain_equal(&mut self, a: usize, b: usize) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        self.ctx().constrain_equal(&a, &b);
+        self.trace_op("constrain_equal", start, Ok(()))
+    }
+
+    pub fn range_check(&self, a: usize, b: &str) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b: usize = parse_int(b)?;
+        self.range.range_check(&mut self.ctx(), a, b);
+        self.trace_op("range_check", start, Ok(()))
+    }
+
+    pub fn check_less_than(
+        &mut self,
+        a: usize,
+        b: usize,
+        size: &str,
+    ) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let size: usize = parse_int(size)?;
+        self.range.check_less_than(&mut self.ctx(), a, b, size);
+        self.trace_op("check_less_than", start, Ok(()))
+    }
+
+    pub fn check_less_than_safe(&mut self, a: usize, b: &str) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b: u64 = parse_int(b)?;
+        self.range.check_less_than_safe(&mut self.ctx(), a, b);
+        self.trace_op("check_less_than_safe", start, Ok(()))
+    }
+
+    pub fn is_less_than(
+        &mut self,
+        a: usize,
+        b: usize,
+        size: &str,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let size: usize = parse_int(size)?;
+        let out = self.range.is_less_than(&mut self.ctx(), a, b, size);
+        self.trace_op("is_less_than", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn is_less_than_safe(&mut self, a: usize, b: &str) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b: u64 = parse_int(b)?;
+        let out = self.range.is_less_than_safe(&mut self.ctx(), a, b);
+        self.trace_op("is_less_than_safe", start, self.to_js_assigned_value(out))
+    }
+
+    pub fn div_mod(&mut self, a: usize, b: &str, size: &str) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b: BigUint = parse_int(b)?;
+        let size: usize = parse_int(size)?;
+        let out = self.range.div_mod(&mut self.ctx(), a, b, size);
         let out = vec![out.0, out.1];
-        self.to_js_assigned_values(out)
+        self.trace_op("div_mod", start, self.to_js_assigned_values(out))
     }
 
     /// Returns a 256-bit hi-lo pair from a single CircuitValue
@@ -336,8 +537,9 @@ impl Halo2LibWasm {
     /// See `check_hi_lo` for what is constrained.
     /// 
     /// * `a`: the CircuitValue to split into hi-lo
-    pub fn to_hi_lo(&mut self, a: usize) -> Vec<u32> {
-        let a = self.get_assigned_value(a);
+    pub fn to_hi_lo(&mut self, a: usize) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
         let a_val = a.value();
         let a_bytes = a_val.to_bytes();
 
@@ -348,18 +550,15 @@ impl Halo2LibWasm {
         let a_lo = Fr::from_bytes(&a_lo_bytes).unwrap();
         let a_hi = Fr::from_bytes(&a_hi_bytes).unwrap();
 
-        let a_lo = self.builder.borrow_mut().main(0).load_witness(a_lo);
-        let a_hi = self.builder.borrow_mut().main(0).load_witness(a_hi);
+        let a_lo = self.ctx().load_witness(a_lo);
+        let a_hi = self.ctx().load_witness(a_hi);
 
         let a_reconstructed = self.check_hi_lo(a_hi, a_lo);
 
-        self.builder
-            .borrow_mut()
-            .main(0)
-            .constrain_equal(&a, &a_reconstructed);
+        self.ctx().constrain_equal(&a, &a_reconstructed);
 
         let out = vec![a_hi, a_lo];
-        self.to_js_assigned_values(out)
+        self.trace_op("to_hi_lo", start, self.to_js_assigned_values(out))
     }
 
     /// Returns a single CircuitValue from a hi-lo pair
@@ -369,13 +568,14 @@ impl Halo2LibWasm {
     /// 
     /// * `hi`: the high 128 bits of the CircuitValue
     /// * `lo`: the low 128 bits of the CircuitValue
-    pub fn from_hi_lo(&mut self, hi: usize, lo: usize) -> usize {
-        let hi = self.get_assigned_value(hi);
-        let lo = self.get_assigned_value(lo);
+    pub fn from_hi_lo(&mut self, hi: usize, lo: usize) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let hi = self.assigned_value(hi)?;
+        let lo = self.assigned_value(lo)?;
 
         let out = self.check_hi_lo(hi, lo);
 
-        self.to_js_assigned_value(out)
+        self.trace_op("from_hi_lo", start, self.to_js_assigned_value(out))
     }
 
     /// Constrains and returns a single CircuitValue from a hi-lo pair
@@ -387,37 +587,27 @@ impl Halo2LibWasm {
         let (hi_max, lo_max) = modulus::<Fr>().div_mod_floor(&(BigUint::one() << 128));
 
         //check hi < r // 2**128
-        let check_1 =
-            self.range
-                .is_big_less_than_safe(self.builder.borrow_mut().main(0), hi, hi_max.clone());
+        let check_1 = self
+            .range
+            .is_big_less_than_safe(&mut self.ctx(), hi, hi_max.clone());
 
         //check (hi == r // 2 ** 128 AND lo < r % 2**128)
         let hi_max_fe = biguint_to_fe::<Fr>(&hi_max);
         let lo_max_fe = biguint_to_fe::<Fr>(&lo_max);
-        let check_2_hi =
-            self.gate
-                .is_equal(self.builder.borrow_mut().main(0), hi, Constant(hi_max_fe));
-        self.range
-            .range_check(self.builder.borrow_mut().main(0), lo, 128);
-        let check_2_lo = self.range.is_less_than(
-            self.builder.borrow_mut().main(0),
-            lo,
-            Constant(lo_max_fe),
-            128,
-        );
-        let check_2 = self
-            .gate
-            .and(self.builder.borrow_mut().main(0), check_2_hi, check_2_lo);
+        let check_2_hi = self.gate.is_equal(&mut self.ctx(), hi, Constant(hi_max_fe));
+        self.range.range_check(&mut self.ctx(), lo, 128);
+        let check_2_lo = self
+            .range
+            .is_less_than(&mut self.ctx(), lo, Constant(lo_max_fe), 128);
+        let check_2 = self.gate.and(&mut self.ctx(), check_2_hi, check_2_lo);
 
         //constrain (check_1 || check_2) == 1
-        let check = self
-            .gate
-            .add(self.builder.borrow_mut().main(0), check_1, check_2);
+        let check = self.gate.add(&mut self.ctx(), check_1, check_2);
         self.gate
-            .assert_is_const(self.builder.borrow_mut().main(0), &check, &Fr::one());
+            .assert_is_const(&mut self.ctx(), &check, &Fr::one());
 
         let combined = self.gate.mul_add(
-            self.builder.borrow_mut().main(0),
+            &mut self.ctx(),
             hi,
             Constant(self.gate.pow_of_two()[128]),
             lo,
@@ -425,72 +615,181 @@ impl Halo2LibWasm {
         combined
     }
 
-    pub fn div_mod_var(&mut self, a: usize, b: usize, a_size: &str, b_size: &str) -> Vec<u32> {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let a_size: usize = a_size.parse().unwrap();
-        let b_size: usize = b_size.parse().unwrap();
+    pub fn div_mod_var(
+        &mut self,
+        a: usize,
+        b: usize,
+        a_size: &str,
+        b_size: &str,
+    ) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let a_size: usize = parse_int(a_size)?;
+        let b_size: usize = parse_int(b_size)?;
         let out = self
             .range
-            .div_mod_var(self.builder.borrow_mut().main(0), a, b, a_size, b_size);
+            .div_mod_var(&mut self.ctx(), a, b, a_size, b_size);
         let out = vec![out.0, out.1];
-        self.to_js_assigned_values(out)
-    }
-
-    pub fn pow_var(&mut self, a: usize, b: usize, max_bits: &str) -> usize {
-        let a = self.get_assigned_value(a);
-        let b = self.get_assigned_value(b);
-        let max_bits: usize = max_bits.parse().unwrap();
-        let out = self
-            .gate
-            .pow_var(self.builder.borrow_mut().main(0), a, b, max_bits);
-        self.to_js_assigned_value(out)
-    }
-
-    pub fn poseidon(&mut self, a: &[u32]) -> usize {
-        let a = self.get_assigned_values(a);
-        let spec = OptimizedPoseidonSpec::<Fr, T, RATE>::new::<R_F, R_P, SECURE_MDS>();
-        let mut hasher = PoseidonHasher::new(spec);
-        hasher.initialize_consts(self.builder.borrow_mut().main(0), self.range.gate());
-        let inputs = a.as_slice();
-        let hash =
-            hasher.hash_fix_len_array(self.builder.borrow_mut().main(0), self.range.gate(), inputs);
-        self.to_js_assigned_value(hash)
-    }
-
-    pub fn witness(&mut self, val: &str) -> usize {
-        let x = Fr::from_str_vartime(val).unwrap();
-        let witness = self.builder.borrow_mut().main(0).load_witness(x);
-        self.to_js_assigned_value(witness)
-    }
-
-    pub fn constant(&mut self, val: &str) -> usize {
-        let idx = Fr::from_str_vartime(val).unwrap();
-        let constant = self.builder.borrow_mut().main(0).load_constant(idx);
-        self.to_js_assigned_value(constant)
-    }
-
-    pub fn make_public(&mut self, circuit: &mut Halo2Wasm, a: usize, col: usize) {
-        let a = self.get_assigned_value(a);
-        let public = circuit.public.get_mut(col).unwrap();
-        public.push(a);
-    }
-
-    pub fn log(&mut self, circuit: &Halo2Wasm, a: usize) {
-        let val = self.value(a);
+        self.trace_op("div_mod_var", start, self.to_js_assigned_values(out))
+    }
+
+    pub fn pow_var(&mut self, a: usize, b: usize, max_bits: &str) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.assigned_value(a)?;
+        let b = self.assigned_value(b)?;
+        let max_bits: usize = parse_int(max_bits)?;
+        let out = self.gate.pow_var(&mut self.ctx(), a, b, max_bits);
+        self.trace_op("pow_var", start, self.to_js_assigned_value(out))
+    }
+
+    /// Loads a witness from a decimal or `0x`-prefixed hex string.
+    ///
+    /// Errors if the value cannot be parsed or is not less than the field modulus.
+    pub fn witness(&mut self, val: &str) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let x = fr_from_str(val)?;
+        self.trace_op("witness", start, self.load_witness(x))
+    }
+
+    /// Loads a witness from a big-endian byte array of at most 32 bytes.
+    #[wasm_bindgen(js_name = witnessBytes)]
+    pub fn witness_bytes(&mut self, val: &[u8]) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let x = fr_from_bytes_be(val)?;
+        self.trace_op("witness", start, self.load_witness(x))
+    }
+
+    /// Loads a witness for each decimal or `0x`-prefixed hex string in `vals`, returning
+    /// their cells in order.
+    ///
+    /// Nothing is loaded if any of the values is invalid.
+    pub fn witnesses(&mut self, vals: Vec<String>) -> Result<Vec<u32>, Halo2WasmError> {
+        let start = self.trace_start();
+        let vals = vals
+            .iter()
+            .map(|val| fr_from_str(val))
+            .collect::<Result<Vec<_>, _>>()?;
+        let witnesses = self.ctx().assign_witnesses(vals);
+        self.trace_op("witnesses", start, self.to_js_assigned_values(witnesses))
+    }
+
+    /// Loads a witness given as a string or `Uint8Array` with the given encoding.
+    #[wasm_bindgen(js_name = witnessEncoded)]
+    pub fn witness_encoded(
+        &mut self,
+        val: JsValue,
+        encoding: FieldEncoding,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let x = decode_js_fr(val, encoding)?;
+        self.trace_op("witness", start, self.load_witness(x))
+    }
+
+    /// Loads a constant given as a string or `Uint8Array` with the given encoding.
+    #[wasm_bindgen(js_name = constantEncoded)]
+    pub fn constant_encoded(
+        &mut self,
+        val: JsValue,
+        encoding: FieldEncoding,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let x = decode_js_fr(val, encoding)?;
+        self.trace_op("constant", start, self.load_constant(x))
+    }
+
+    /// Loads a constant from a decimal or `0x`-prefixed hex string.
+    ///
+    /// Errors if the value cannot be parsed or is not less than the field modulus.
+    pub fn constant(&mut self, val: &str) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let x = fr_from_str(val)?;
+        self.trace_op("constant", start, self.load_constant(x))
+    }
+
+    /// Loads a constant from a big-endian byte array of at most 32 bytes.
+    #[wasm_bindgen(js_name = constantBytes)]
+    pub fn constant_bytes(&mut self, val: &[u8]) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let x = fr_from_bytes_be(val)?;
+        self.trace_op("constant", start, self.load_constant(x))
+    }
+
+    pub fn make_public(
+        &mut self,
+        circuit: &mut Halo2Wasm,
+        a: usize,
+        col: usize,
+    ) -> Result<(), Halo2WasmError> {
+        circuit.instance_column(col)?;
+        let a = self.assigned_value(a)?;
+        circuit.public[col].push(a);
+        Ok(())
+    }
+
+    pub fn log(&mut self, circuit: &Halo2Wasm, a: usize) -> Result<(), Halo2WasmError> {
+        let val = self.value(a)?;
         unsafe {
             circuit.log(val);
         }
+        Ok(())
+    }
+
+    /// Returns the value of `a` as a `0x`-prefixed big-endian hex string.
+    pub fn value(&mut self, a: usize) -> Result<String, Halo2WasmError> {
+        let a = self.assigned_value(a)?;
+        Ok(fr_to_hex(a.value()))
     }
 
-    pub fn value(&mut self, a: usize) -> String {
-        let a = self.get_assigned_value(a);
-        let value = a.value();
-        let value_str = format!("{value:?}");
-        value_str
+    /// Returns the value of `a` with the given encoding.
+    #[wasm_bindgen(js_name = valueEncoded)]
+    pub fn value_encoded(
+        &mut self,
+        a: usize,
+        encoding: FieldEncoding,
+    ) -> Result<JsValue, Halo2WasmError> {
+        let a = self.assigned_value(a)?;
+        Ok(encode_fr(a.value(), encoding).into())
     }
 
     pub fn lookup_bits(&mut self) -> usize {
         self.range.lookup_bits()
     }
 }
+
+impl Halo2LibWasm {
+    /// Runs `f` on each input in a new thread, in parallel with rayon,
+    /// returning the outputs in order. Ops keep being assigned in the current thread.
+    ///
+    /// Values assigned by `f` can be passed to other ops with [`Self::to_js_assigned_value`].
+    pub fn parallelize<T, R, F>(&mut self, inputs: Vec<T>, f: F) -> Vec<R>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(&mut Context<Fr>, &RangeChip<Fr>, T) -> R + Send + Sync,
+    {
+        let thread = self.ctx().id();
+        self.select_thread(thread);
+        let range = &self.range;
+        let mut builder = self.builder.borrow_mut();
+        parallelize_core(builder.pool(0), inputs, |ctx, input| {
+            f(ctx, range, input)
+        })
+    }
+
+    /// Returns the cell referred to by `handle`, or an error if the handle
+    /// belongs to another circuit, was created before the circuit was cleared, or is not assigned.
+    pub fn assigned_value(&self, handle: usize) -> Result<AssignedValue<Fr>, Halo2WasmError> {
+        let builder = self.builder.borrow();
+        let threads = &builder.core().phase_manager[0].threads;
+        self.handles.borrow().lookup(threads, handle)
+    }
+
+    /// Returns the handle of `a` passed to and from JS, or an error if all handles are taken.
+    pub fn to_js_assigned_value(&self, a: AssignedValue<Fr>) -> Result<usize, Halo2WasmError> {
+        let cell = a.cell.unwrap();
+        self.handles
+            .borrow_mut()
+            .handle(cell.context_id, cell.offset)
+    }
+}
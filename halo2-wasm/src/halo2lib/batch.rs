@@ -0,0 +1,318 @@
+//! Executes a buffer of operations in a single call, avoiding a JS to wasm call per op.
+//!
+//! A batch is a `Uint32Array` of instructions, each an opcode from [`BatchOp`] followed by its
+//! operands. Cell operands are value handles, or the index of an earlier output of the same batch
+//! with [`BATCH_OUTPUT_FLAG`] set. Witnesses and constants refer to a string in the `values`
+//! array, and bit sizes are immediates. The value handles of all outputs are returned in order.
+use halo2_base::{
+    gates::{GateInstructions, RangeInstructions},
+    halo2_proofs::halo2curves::ff::PrimeField,
+    AssignedValue,
+};
+use wasm_bindgen::prelude::*;
+
+use super::{Fr, Halo2LibWasm};
+use crate::{utils::fr_from_str, Halo2WasmError};
+
+/// Operands with this bit set refer to the output of an earlier op in the same batch.
+pub const BATCH_OUTPUT_FLAG: u32 = 1 << 31;
+
+/// Opcodes of a batch, see `executeBatch`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchOp {
+    /// `value` → cell
+    Witness = 0,
+    /// `value` → cell
+    Constant = 1,
+    /// `a, b` → cell
+    Add = 2,
+    /// `a, b` → cell
+    Sub = 3,
+    /// `a` → cell
+    Neg = 4,
+    /// `a, b` → cell
+    Mul = 5,
+    /// `a, b, c` → cell
+    MulAdd = 6,
+    /// `a, b` → cell
+    MulNot = 7,
+    /// `a, b` → cell
+    DivUnsafe = 8,
+    /// `a, b` → cell
+    And = 9,
+    /// `a, b` → cell
+    Or = 10,
+    /// `a` → cell
+    Not = 11,
+    /// `a` → cell
+    Dec = 12,
+    /// `a, b, sel` → cell
+    Select = 13,
+    /// `a, b, c` → cell
+    OrAnd = 14,
+    /// `a` → cell
+    IsZero = 15,
+    /// `a, b` → cell
+    IsEqual = 16,
+    /// `a`
+    AssertBit = 17,
+    /// `a, b`
+    ConstrainEqual = 18,
+    /// `a, bits`
+    RangeCheck = 19,
+    /// `a, b, bits`
+    CheckLessThan = 20,
+    /// `a, b, bits` → cell
+    IsLessThan = 21,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    Cell,
+    Value,
+    Bits,
+}
+
+impl BatchOp {
+    const ALL: [BatchOp; 22] = [
+        BatchOp::Witness,
+        BatchOp::Constant,
+        BatchOp::Add,
+        BatchOp::Sub,
+        BatchOp::Neg,
+        BatchOp::Mul,
+        BatchOp::MulAdd,
+        BatchOp::MulNot,
+        BatchOp::DivUnsafe,
+        BatchOp::And,
+        BatchOp::Or,
+        BatchOp::Not,
+        BatchOp::Dec,
+        BatchOp::Select,
+        BatchOp::OrAnd,
+        BatchOp::IsZero,
+        BatchOp::IsEqual,
+        BatchOp::AssertBit,
+        BatchOp::ConstrainEqual,
+        BatchOp::RangeCheck,
+        BatchOp::CheckLessThan,
+        BatchOp::IsLessThan,
+    ];
+
+    fn from_u32(opcode: u32) -> Option<Self> {
+        Self::ALL.get(opcode as usize).copied()
+    }
+
+    /// Name of the equivalent `Halo2LibWasm` method, used for debug provenance.
+    fn name(self) -> &'static str {
+        match self {
+            BatchOp::Witness => "witness",
+            BatchOp::Constant => "constant",
+            BatchOp::Add => "add",
+            BatchOp::Sub => "sub",
+            BatchOp::Neg => "neg",
+            BatchOp::Mul => "mul",
+            BatchOp::MulAdd => "mul_add",
+            BatchOp::MulNot => "mul_not",
+            BatchOp::DivUnsafe => "div_unsafe",
+            BatchOp::And => "and",
+            BatchOp::Or => "or",
+            BatchOp::Not => "not",
+            BatchOp::Dec => "dec",
+            BatchOp::Select => "select",
+            BatchOp::OrAnd => "or_and",
+            BatchOp::IsZero => "is_zero",
+            BatchOp::IsEqual => "is_equal",
+            BatchOp::AssertBit => "assert_bit",
+            BatchOp::ConstrainEqual => "constrain_equal",
+            BatchOp::RangeCheck => "range_check",
+            BatchOp::CheckLessThan => "check_less_than",
+            BatchOp::IsLessThan => "is_less_than",
+        }
+    }
+
+    fn operands(self) -> &'static [Operand] {
+        use Operand::*;
+        match self {
+            BatchOp::Witness | BatchOp::Constant => &[Value],
+            BatchOp::Neg | BatchOp::Not | BatchOp::Dec | BatchOp::IsZero | BatchOp::AssertBit => {
+                &[Cell]
+            }
+            BatchOp::Add
+            | BatchOp::Sub
+            | BatchOp::Mul
+            | BatchOp::MulNot
+            | BatchOp::DivUnsafe
+            | BatchOp::And
+            | BatchOp::Or
+            | BatchOp::IsEqual
+            | BatchOp::ConstrainEqual => &[Cell, Cell],
+            BatchOp::MulAdd | BatchOp::Select | BatchOp::OrAnd => &[Cell, Cell, Cell],
+            BatchOp::RangeCheck => &[Cell, Bits],
+            BatchOp::CheckLessThan | BatchOp::IsLessThan => &[Cell, Cell, Bits],
+        }
+    }
+
+    /// Largest bit size operand the range chip accepts for `self`.
+    fn max_bits(self, lookup_bits: usize) -> usize {
+        let capacity = Fr::CAPACITY as usize;
+        match self {
+            // `is_less_than` rounds the bits up to whole limbs and needs one more limb on top
+            BatchOp::IsLessThan => (capacity / lookup_bits - 1) * lookup_bits,
+            _ => capacity,
+        }
+    }
+
+    fn has_output(self) -> bool {
+        !matches!(
+            self,
+            BatchOp::AssertBit
+                | BatchOp::ConstrainEqual
+                | BatchOp::RangeCheck
+                | BatchOp::CheckLessThan
+        )
+    }
+}
+
+struct Instruction<'a> {
+    op: BatchOp,
+    args: &'a [u32],
+}
+
+/// Splits `ops` into instructions, checking opcodes and that every operand is in range.
+fn decode(
+    ops: &[u32],
+    num_values: usize,
+    lookup_bits: usize,
+    is_assigned: impl Fn(usize) -> bool,
+) -> Result<Vec<Instruction<'_>>, Halo2WasmError> {
+    let mut instructions = vec![];
+    let mut num_outputs = 0;
+    let mut i = 0;
+    while i < ops.len() {
+        let op = BatchOp::from_u32(ops[i]).ok_or_else(|| {
+            Halo2WasmError::InvalidBatch(format!("unknown opcode {} at {i}", ops[i]))
+        })?;
+        let operands = op.operands();
+        let args = ops.get(i + 1..i + 1 + operands.len()).ok_or_else(|| {
+            Halo2WasmError::InvalidBatch(format!("{op:?} at {i} is missing operands"))
+        })?;
+        for (kind, &arg) in operands.iter().zip(args) {
+            let in_range = match kind {
+                Operand::Cell if arg & BATCH_OUTPUT_FLAG != 0 => {
+                    ((arg & !BATCH_OUTPUT_FLAG) as usize) < num_outputs
+                }
+                Operand::Cell => is_assigned(arg as usize),
+                Operand::Value => (arg as usize) < num_values,
+                Operand::Bits => (arg as usize) <= op.max_bits(lookup_bits),
+            };
+            if !in_range {
+                return Err(Halo2WasmError::InvalidBatch(format!(
+                    "{kind:?} operand {arg:#x} of {op:?} at {i} is out of range"
+                )));
+            }
+        }
+        if op.has_output() {
+            num_outputs += 1;
+        }
+        instructions.push(Instruction { op, args });
+        i += 1 + operands.len();
+    }
+    Ok(instructions)
+}
+
+#[wasm_bindgen]
+impl Halo2LibWasm {
+    /// Executes a batch of operations encoded as described in [`BatchOp`], returning the
+    /// value handles of their outputs in order.
+    ///
+    /// The whole batch is validated before any op is assigned, including that bit sizes are
+    /// within the limits of the range chip for the configured `lookup_bits`.
+    #[wasm_bindgen(js_name = executeBatch)]
+    pub fn execute_batch(
+        &mut self,
+        ops: &[u32],
+        values: Vec<String>,
+    ) -> Result<Vec<u32>, Halo2WasmError> {
+        self.execute_ops(ops, &values)
+    }
+}
+
+impl Halo2LibWasm {
+    /// Native version of `executeBatch`.
+    pub fn execute_ops(
+        &mut self,
+        ops: &[u32],
+        values: &[String],
+    ) -> Result<Vec<u32>, Halo2WasmError> {
+        let values = values
+            .iter()
+            .map(|x| fr_from_str(x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let lookup_bits = self.range.lookup_bits();
+        let instructions = decode(ops, values.len(), lookup_bits, |handle| {
+            self.is_assigned(handle)
+        })?;
+
+        let mut outputs: Vec<AssignedValue<Fr>> = Vec::new();
+        for Instruction { op, args } in instructions {
+            // cell operands always precede values and bit sizes
+            let cells = op
+                .operands()
+                .iter()
+                .zip(args)
+                .filter(|(kind, _)| **kind == Operand::Cell)
+                .map(|(_, &arg)| match arg & BATCH_OUTPUT_FLAG {
+                    0 => self.assigned_value(arg as usize),
+                    _ => Ok(outputs[(arg & !BATCH_OUTPUT_FLAG) as usize]),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let start = self.trace_start();
+            let mut ctx = self.ctx();
+            let ctx = &mut *ctx;
+            let (gate, range) = (&self.gate, &self.range);
+            let out = match op {
+                BatchOp::Witness => Some(ctx.load_witness(values[args[0] as usize])),
+                BatchOp::Constant => Some(ctx.load_constant(values[args[0] as usize])),
+                BatchOp::Add => Some(gate.add(ctx, cells[0], cells[1])),
+                BatchOp::Sub => Some(gate.sub(ctx, cells[0], cells[1])),
+                BatchOp::Neg => Some(gate.neg(ctx, cells[0])),
+                BatchOp::Mul => Some(gate.mul(ctx, cells[0], cells[1])),
+                BatchOp::MulAdd => Some(gate.mul_add(ctx, cells[0], cells[1], cells[2])),
+                BatchOp::MulNot => Some(gate.mul_not(ctx, cells[0], cells[1])),
+                BatchOp::DivUnsafe => Some(gate.div_unsafe(ctx, cells[0], cells[1])),
+                BatchOp::And => Some(gate.and(ctx, cells[0], cells[1])),
+                BatchOp::Or => Some(gate.or(ctx, cells[0], cells[1])),
+                BatchOp::Not => Some(gate.not(ctx, cells[0])),
+                BatchOp::Dec => Some(gate.dec(ctx, cells[0])),
+                BatchOp::Select => Some(gate.select(ctx, cells[0], cells[1], cells[2])),
+                BatchOp::OrAnd => Some(gate.or_and(ctx, cells[0], cells[1], cells[2])),
+                BatchOp::IsZero => Some(gate.is_zero(ctx, cells[0])),
+                BatchOp::IsEqual => Some(gate.is_equal(ctx, cells[0], cells[1])),
+                BatchOp::AssertBit => {
+                    gate.assert_bit(ctx, cells[0]);
+                    None
+                }
+                BatchOp::ConstrainEqual => {
+                    ctx.constrain_equal(&cells[0], &cells[1]);
+                    None
+                }
+                BatchOp::RangeCheck => {
+                    range.range_check(ctx, cells[0], args[1] as usize);
+                    None
+                }
+                BatchOp::CheckLessThan => {
+                    range.check_less_than(ctx, cells[0], cells[1], args[2] as usize);
+                    None
+                }
+                BatchOp::IsLessThan => {
+                    Some(range.is_less_than(ctx, cells[0], cells[1], args[2] as usize))
+                }
+            };
+            outputs.extend(out);
+            self.record_op(op.name(), start);
+        }
+        self.to_js_assigned_values(outputs)
+    }
+}
@@ -0,0 +1,30 @@
+//! Gadgets on individual bits, used by the hash functions `halo2-base` has no chips for.
+use halo2_base::{
+    gates::{GateChip, GateInstructions},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+
+use super::Fr;
+
+/// Returns `a ^ b` for bits `a` and `b`.
+pub(super) fn xor(
+    ctx: &mut Context<Fr>,
+    gate: &GateChip<Fr>,
+    a: AssignedValue<Fr>,
+    b: AssignedValue<Fr>,
+) -> AssignedValue<Fr> {
+    // a ^ b = a + b * (1 - 2a)
+    let not_2a = gate.mul_add(ctx, a, Constant(-Fr::from(2)), Constant(Fr::from(1)));
+    gate.mul_add(ctx, b, not_2a, a)
+}
+
+/// Returns the value of the little-endian `bits`.
+pub(super) fn bits_to_num(
+    ctx: &mut Context<Fr>,
+    gate: &GateChip<Fr>,
+    bits: &[AssignedValue<Fr>],
+) -> AssignedValue<Fr> {
+    let powers = gate.pow_of_two()[..bits.len()].iter().map(|x| Constant(*x));
+    gate.inner_product(ctx, bits.iter().copied(), powers)
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+/// An operation recorded in debug mode, covering the offsets `[start, next op's start)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracedOp {
+    pub op: &'static str,
+    pub label: Option<String>,
+    pub start: usize,
+}
+
+impl std::fmt::Display for TracedOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{} at {}", self.op, label),
+            None => write!(f, "{}", self.op),
+        }
+    }
+}
+
+/// Records which `Halo2LibWasm` operation assigned each offset of the threads of a phase.
+#[derive(Clone, Debug, Default)]
+pub struct OpTrace {
+    /// Handle generation of the circuit the ops were recorded in.
+    generation: u64,
+    /// Ops of each thread, ordered by start offset.
+    threads: BTreeMap<usize, Vec<TracedOp>>,
+    label: Option<String>,
+}
+
+impl OpTrace {
+    /// Sets the label attached to every following operation, e.g. a JS call site.
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    /// Records `op` as starting at offset `start` of `thread`, the length of its context before
+    /// the op ran, in the circuit with handle generation `generation`.
+    ///
+    /// Ops of a previous generation are dropped, as the circuit has been cleared since.
+    pub fn record(&mut self, generation: u64, thread: usize, op: &'static str, start: usize) {
+        if generation != self.generation {
+            self.generation = generation;
+            self.threads.clear();
+        }
+        let ops = self.threads.entry(thread).or_default();
+        // Ops are recorded once they finish, so any ops they are built from are already recorded
+        // and get replaced.
+        let valid = ops.partition_point(|traced| traced.start < start);
+        ops.truncate(valid);
+        ops.push(TracedOp {
+            op,
+            label: self.label.clone(),
+            start,
+        });
+    }
+
+    /// Returns the operation that assigned the cell at `offset` of `thread`, if it was recorded in
+    /// the circuit with handle generation `generation`.
+    pub fn find(&self, generation: u64, thread: usize, offset: usize) -> Option<&TracedOp> {
+        if generation != self.generation {
+            return None;
+        }
+        let ops = self.threads.get(&thread)?;
+        let idx = ops.partition_point(|traced| traced.start <= offset);
+        idx.checked_sub(1).map(|idx| &ops[idx])
+    }
+}
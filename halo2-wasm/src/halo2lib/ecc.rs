@@ -39,10 +39,14 @@ impl Bn254FqPoint {
     fn to_hi_lo(&self, lib_wasm: &Halo2LibWasm) -> [AssignedValue<Fr>; 2] {
         convert_3limbs88bits_to_hi_lo(lib_wasm, self.0.limbs())
     }
-    pub fn to_circuit_value_256(&self, lib_wasm: &Halo2LibWasm) -> JsCircuitValue256 {
+    pub fn to_circuit_value_256(
+        &self,
+        lib_wasm: &Halo2LibWasm,
+    ) -> Result<JsCircuitValue256, Halo2WasmError> {
         let [hi, lo] = self.to_hi_lo(lib_wasm);
-        let [hi, lo] = [hi, lo].map(|x| lib_wasm.to_js_assigned_value(x));
-        JsCircuitValue256 { hi, lo }
+        let hi = lib_wasm.to_js_assigned_value(hi)?;
+        let lo = lib_wasm.to_js_assigned_value(lo)?;
+        Ok(JsCircuitValue256 { hi, lo })
     }
 }
 
@@ -99,10 +103,14 @@ impl Secp256k1FpPoint {
     fn to_hi_lo(&self, lib_wasm: &Halo2LibWasm) -> [AssignedValue<Fr>; 2] {
         convert_3limbs88bits_to_hi_lo(lib_wasm, self.0.limbs())
     }
-    pub fn to_circuit_value_256(&self, lib_wasm: &Halo2LibWasm) -> JsCircuitValue256 {
+    pub fn to_circuit_value_256(
+        &self,
+        lib_wasm: &Halo2LibWasm,
+    ) -> Result<JsCircuitValue256, Halo2WasmError> {
         let [hi, lo] = self.to_hi_lo(lib_wasm);
-        let [hi, lo] = [hi, lo].map(|x| lib_wasm.to_js_assigned_value(x));
-        JsCircuitValue256 { hi, lo }
+        let hi = lib_wasm.to_js_assigned_value(hi)?;
+        let lo = lib_wasm.to_js_assigned_value(lo)?;
+        Ok(JsCircuitValue256 { hi, lo })
     }
 }
 
@@ -117,10 +125,14 @@ impl Secp256k1FqPoint {
     fn to_hi_lo(&self, lib_wasm: &Halo2LibWasm) -> [AssignedValue<Fr>; 2] {
         convert_3limbs88bits_to_hi_lo(lib_wasm, self.0.limbs())
     }
-    pub fn to_circuit_value_256(&self, lib_wasm: &Halo2LibWasm) -> JsCircuitValue256 {
+    pub fn to_circuit_value_256(
+        &self,
+        lib_wasm: &Halo2LibWasm,
+    ) -> Result<JsCircuitValue256, Halo2WasmError> {
         let [hi, lo] = self.to_hi_lo(lib_wasm);
-        let [hi, lo] = [hi, lo].map(|x| lib_wasm.to_js_assigned_value(x));
-        JsCircuitValue256 { hi, lo }
+        let hi = lib_wasm.to_js_assigned_value(hi)?;
+        let lo = lib_wasm.to_js_assigned_value(lo)?;
+        Ok(JsCircuitValue256 { hi, lo })
     }
 }
 
@@ -232,33 +244,48 @@ impl Halo2LibWasm {
 
     /// Takes in CircuitValue256 in hi-lo form and loads internal CircuitBn254Fq type (we use 3 limbs of 88 bits).
     /// This function does not range check `hi,lo` to be `uint128` in case it's already done elsewhere.
-    pub fn load_bn254_fq(&self, val: JsCircuitValue256) -> Bn254FqPoint {
+    pub fn load_bn254_fq(&self, val: JsCircuitValue256) -> Result<Bn254FqPoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
-        Bn254FqPoint(self.load_generic_fp_impl::<Bn254Fq>(&fq_chip, val))
+        let out = self.load_generic_fp_impl::<Bn254Fq>(&fq_chip, val)?;
+        self.trace_op("load_bn254_fq", start, Ok(Bn254FqPoint(out)))
     }
     /// Doesn't range check limbs of g1_point.
     /// Does not allow you to load identity point.
-    pub fn load_bn254_g1(&self, point: JsCircuitBn254G1Affine) -> Bn254G1AffinePoint {
+    pub fn load_bn254_g1(
+        &self,
+        point: JsCircuitBn254G1Affine,
+    ) -> Result<Bn254G1AffinePoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
         let g1_chip = EccChip::new(&fq_chip);
-        self.load_bn254_g1_impl(&g1_chip, point)
+        self.trace_op(
+            "load_bn254_g1",
+            start,
+            self.load_bn254_g1_impl(&g1_chip, point),
+        )
     }
     /// `g1_points` should be array of `CircuitBn254G1Affine` in hi-lo form.
     /// This function does not range check `hi,lo` to be `uint128` in case it's already done elsewhere.
     /// Prevents any g1_points from being identity.
-    pub fn bn254_g1_sum(&self, g1_points: js_sys::Array) -> Bn254G1AffinePoint {
+    pub fn bn254_g1_sum(
+        &self,
+        g1_points: js_sys::Array,
+    ) -> Result<Bn254G1AffinePoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
         let g1_chip = EccChip::new(&fq_chip);
-        let g1_points: Vec<JsCircuitBn254G1Affine> = g1_points
+        let g1_points = g1_points
             .iter()
-            .map(|x| serde_wasm_bindgen::from_value(x).unwrap())
-            .collect();
-        let g1_points: Vec<_> = g1_points
+            .map(serde_wasm_bindgen::from_value)
+            .collect::<Result<Vec<JsCircuitBn254G1Affine>, _>>()
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        let g1_points = g1_points
             .into_iter()
-            .map(|point| self.load_bn254_g1_impl(&g1_chip, point).0)
-            .collect();
-        let sum = g1_chip.sum::<Bn254G1Affine>(self.builder.borrow_mut().main(0), g1_points);
-        Bn254G1AffinePoint(sum)
+            .map(|point| Ok(self.load_bn254_g1_impl(&g1_chip, point)?.0))
+            .collect::<Result<Vec<_>, Halo2WasmError>>()?;
+        let sum = g1_chip.sum::<Bn254G1Affine>(&mut self.ctx(), g1_points);
+        self.trace_op("bn254_g1_sum", start, Ok(Bn254G1AffinePoint(sum)))
     }
 
     /// `g1_point_1` and `g1_point_2` are `CircuitBn254G1Affine` points in hi-lo form.
@@ -269,47 +296,56 @@ impl Halo2LibWasm {
         &self,
         g1_point_1: JsCircuitBn254G1Affine,
         g1_point_2: JsCircuitBn254G1Affine,
-    ) -> Bn254G1AffinePoint {
+    ) -> Result<Bn254G1AffinePoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
         let g1_chip = EccChip::new(&fq_chip);
         let g1_point_1_loaded: EcPoint<Fr, FqPoint> =
-            self.load_bn254_g1_impl(&g1_chip, g1_point_1).0;
+            self.load_bn254_g1_impl(&g1_chip, g1_point_1)?.0;
         let g1_point_2_loaded: EcPoint<Fr, FqPoint> =
-            self.load_bn254_g1_impl(&g1_chip, g1_point_2).0;
-        let diff = g1_chip.sub_unequal(
-            self.builder.borrow_mut().main(0),
-            g1_point_1_loaded,
-            g1_point_2_loaded,
-            true,
-        );
-        Bn254G1AffinePoint(diff)
+            self.load_bn254_g1_impl(&g1_chip, g1_point_2)?.0;
+        let diff = g1_chip.sub_unequal(&mut self.ctx(), g1_point_1_loaded, g1_point_2_loaded, true);
+        self.trace_op("bn254_g1_sub_unequal", start, Ok(Bn254G1AffinePoint(diff)))
     }
 
     /// Doesn't range check limbs of g2_point.
     /// Does not allow you to load identity point.
-    pub fn load_bn254_g2(&self, point: JsCircuitBn254G2Affine) -> Bn254G2AffinePoint {
+    pub fn load_bn254_g2(
+        &self,
+        point: JsCircuitBn254G2Affine,
+    ) -> Result<Bn254G2AffinePoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
         let fq2_chip = Bn254Fq2Chip::new(&fq_chip);
         let g2_chip = EccChip::new(&fq2_chip);
-        self.load_bn254_g2_impl(&g2_chip, point)
+        self.trace_op(
+            "load_bn254_g2",
+            start,
+            self.load_bn254_g2_impl(&g2_chip, point),
+        )
     }
     /// `g2_points` should be array of `CircuitBn254G2Affine` in hi-lo form.
     /// This function does not range check `hi,lo` to be `uint128` in case it's already done elsewhere.
     /// Prevents any g2_points from being identity.
-    pub fn bn254_g2_sum(&self, g2_points: js_sys::Array) -> Bn254G2AffinePoint {
+    pub fn bn254_g2_sum(
+        &self,
+        g2_points: js_sys::Array,
+    ) -> Result<Bn254G2AffinePoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
         let fq2_chip = Bn254Fq2Chip::new(&fq_chip);
         let g2_chip = EccChip::new(&fq2_chip);
-        let g2_points: Vec<JsCircuitBn254G2Affine> = g2_points
+        let g2_points = g2_points
             .iter()
-            .map(|x| serde_wasm_bindgen::from_value(x).unwrap())
-            .collect();
-        let g2_points: Vec<_> = g2_points
+            .map(serde_wasm_bindgen::from_value)
+            .collect::<Result<Vec<JsCircuitBn254G2Affine>, _>>()
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        let g2_points = g2_points
             .into_iter()
-            .map(|point| self.load_bn254_g2_impl(&g2_chip, point).0)
-            .collect();
-        let sum = g2_chip.sum::<Bn254G2Affine>(self.builder.borrow_mut().main(0), g2_points);
-        Bn254G2AffinePoint(sum)
+            .map(|point| Ok(self.load_bn254_g2_impl(&g2_chip, point)?.0))
+            .collect::<Result<Vec<_>, Halo2WasmError>>()?;
+        let sum = g2_chip.sum::<Bn254G2Affine>(&mut self.ctx(), g2_points);
+        self.trace_op("bn254_g2_sum", start, Ok(Bn254G2AffinePoint(sum)))
     }
     /// Verifies that e(lhs_g1, lhs_g2) = e(rhs_g1, rhs_g2) by checking e(lhs_g1, lhs_g2)*e(-rhs_g1, rhs_g2) === 1
     /// Returns [CircuitValue] for the result as a boolean (1 if signature verification is successful).
@@ -320,11 +356,12 @@ impl Halo2LibWasm {
         lhs_g2: Bn254G2AffinePoint,
         rhs_g1: Bn254G1AffinePoint,
         rhs_g2: Bn254G2AffinePoint,
-    ) -> JsCircuitValue {
+    ) -> Result<JsCircuitValue, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.bn254_fq_chip();
         let g1_chip = EccChip::new(&fq_chip);
-        let mut builder = self.builder.borrow_mut();
-        let ctx = builder.main(0);
+        let mut ctx = self.ctx();
+        let ctx = &mut *ctx;
         let neg_rhs_g1 = g1_chip.negate(ctx, rhs_g1.0);
         let pairing_chip = PairingChip::new(&fq_chip);
 
@@ -334,20 +371,28 @@ impl Halo2LibWasm {
         let result = fq12_chip.final_exp(ctx, multi_paired);
         let fq12_one = fq12_chip.load_constant(ctx, Bn254Fq12::one());
         let verification_result = fq12_chip.is_equal(ctx, result, fq12_one);
-        self.to_js_assigned_value(verification_result)
+        self.trace_op(
+            "bn254_pairing_check",
+            start,
+            self.to_js_assigned_value(verification_result),
+        )
     }
 
     /// Doesn't range check limbs of point.
     /// Pubkey is a point on
-    pub fn load_secp256k1_pubkey(&self, point: JsCircuitSecp256k1Affine) -> Secp256k1AffinePoint {
+    pub fn load_secp256k1_pubkey(
+        &self,
+        point: JsCircuitSecp256k1Affine,
+    ) -> Result<Secp256k1AffinePoint, Halo2WasmError> {
+        let start = self.trace_start();
         let fp_chip = self.secp256k1_fp_chip();
-        let [x, y] =
-            [point.x, point.y].map(|c| self.load_generic_fp_impl::<Secp256k1Fp>(&fp_chip, c));
+        let x = self.load_generic_fp_impl::<Secp256k1Fp>(&fp_chip, point.x)?;
+        let y = self.load_generic_fp_impl::<Secp256k1Fp>(&fp_chip, point.y)?;
         let pt = EcPoint::new(x, y);
         let chip = EccChip::new(&fp_chip);
         // this prevents pubkey from being identity point:
-        chip.assert_is_on_curve::<Secp256k1Affine>(self.builder.borrow_mut().main(0), &pt);
-        Secp256k1AffinePoint(pt)
+        chip.assert_is_on_curve::<Secp256k1Affine>(&mut self.ctx(), &pt);
+        self.trace_op("load_secp256k1_pubkey", start, Ok(Secp256k1AffinePoint(pt)))
     }
 
     /// Assumes all `JsCircuitValue256` limbs have been range checked to be `u128`.
@@ -357,21 +402,34 @@ impl Halo2LibWasm {
         r: JsCircuitValue256,
         s: JsCircuitValue256,
         msg_hash: JsCircuitValue256,
-    ) -> JsCircuitValue {
+    ) -> Result<JsCircuitValue, Halo2WasmError> {
+        let start = self.trace_start();
         let fq_chip = self.secp256k1_fq_chip();
         let fp_chip = self.secp256k1_fp_chip();
-        let [r, s, msg_hash] = [r, s, msg_hash].map(|x| self.load_generic_fp_impl(&fq_chip, x));
+        let r = self.load_generic_fp_impl(&fq_chip, r)?;
+        let s = self.load_generic_fp_impl(&fq_chip, s)?;
+        let msg_hash = self.load_generic_fp_impl(&fq_chip, msg_hash)?;
         let ecc_chip = EccChip::new(&fp_chip);
-        let mut builder = self.builder.borrow_mut();
-        let ctx = builder.main(0);
+        let mut ctx = self.ctx();
+        let ctx = &mut *ctx;
         let verification_result =
             ecdsa_verify_no_pubkey_check::<Fr, Secp256k1Fp, Secp256k1Fq, Secp256k1Affine>(
                 &ecc_chip, ctx, pubkey.0, r, s, msg_hash, 4, 4,
             );
-        self.to_js_assigned_value(verification_result)
-    }
-
-    pub fn ecdsa_benchmark(&mut self, sk: u64, msg_hash: u64, k: u64) -> usize {
+        self.trace_op(
+            "verify_secp256k1_ecdsa_signature",
+            start,
+            self.to_js_assigned_value(verification_result),
+        )
+    }
+
+    pub fn ecdsa_benchmark(
+        &mut self,
+        sk: u64,
+        msg_hash: u64,
+        k: u64,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
         // let pk = self.get_assigned_values(pk);
         // let r = self.get_assigned_value(r);
         // let s = self.get_assigned_value(s);
@@ -396,8 +454,8 @@ impl Halo2LibWasm {
         let fp_chip = self.secp256k1_fp_chip();
         let fq_chip = self.secp256k1_fq_chip();
 
-        let mut builder = self.builder.borrow_mut();
-        let ctx = builder.main(0);
+        let mut ctx = self.ctx();
+        let ctx = &mut *ctx;
         let [m, r, s] = [msg_hash, r, s].map(|x| fq_chip.load_private(ctx, x));
 
         let ecc_chip = EccChip::new(&fp_chip);
@@ -407,7 +465,7 @@ impl Halo2LibWasm {
             &ecc_chip, ctx, pk, r, s, m, 4, 4,
         );
 
-        self.to_js_assigned_value(res)
+        self.trace_op("ecdsa_benchmark", start, self.to_js_assigned_value(res))
     }
 
     pub fn to_js_circuit_value_256(&self, hi: usize, lo: usize) -> JsCircuitValue256 {
@@ -417,28 +475,44 @@ impl Halo2LibWasm {
         }
     }
 
-    pub fn to_js_circuit_bn254_g1_affine(&self, x: JsCircuitValue256, y: JsCircuitValue256) -> JsCircuitBn254G1Affine {
+    pub fn to_js_circuit_bn254_g1_affine(
+        &self,
+        x: JsCircuitValue256,
+        y: JsCircuitValue256,
+    ) -> JsCircuitBn254G1Affine {
         JsCircuitBn254G1Affine {
             x,
             y
         }
     }
 
-    pub fn to_js_circuit_bn254_fq2(&self, c0: JsCircuitValue256, c1: JsCircuitValue256) -> JsCircuitBn254Fq2 {
+    pub fn to_js_circuit_bn254_fq2(
+        &self,
+        c0: JsCircuitValue256,
+        c1: JsCircuitValue256,
+    ) -> JsCircuitBn254Fq2 {
         JsCircuitBn254Fq2 {
             c0,
             c1
         }
     }
 
-    pub fn to_js_circuit_bn254_g2_affine(&self, x: JsCircuitBn254Fq2, y: JsCircuitBn254Fq2) -> JsCircuitBn254G2Affine {
+    pub fn to_js_circuit_bn254_g2_affine(
+        &self,
+        x: JsCircuitBn254Fq2,
+        y: JsCircuitBn254Fq2,
+    ) -> JsCircuitBn254G2Affine {
         JsCircuitBn254G2Affine {
             x,
             y
         }
     }
 
-    pub fn to_js_circuit_secp256k1_affine(&self, x: JsCircuitValue256, y: JsCircuitValue256) -> JsCircuitSecp256k1Affine {
+    pub fn to_js_circuit_secp256k1_affine(
+        &self,
+        x: JsCircuitValue256,
+        y: JsCircuitValue256,
+    ) -> JsCircuitSecp256k1Affine {
         JsCircuitSecp256k1Affine {
             x,
             y
@@ -452,47 +526,52 @@ impl Halo2LibWasm {
         &self,
         fp_chip: &FpChip<Fr, Fp>,
         val: JsCircuitValue256,
-    ) -> ProperCrtUint<Fr> {
+    ) -> Result<ProperCrtUint<Fr>, Halo2WasmError> {
         // easiest to just construct the raw bigint, load it as witness, and then constrain against provided circuit value
-        let [hi, lo] = [val.hi, val.lo].map(|x| self.get_assigned_value(x));
+        let hi = self.assigned_value(val.hi)?;
+        let lo = self.assigned_value(val.lo)?;
         let [hi_val, lo_val] = [hi, lo].map(|x| fe_to_biguint(x.value()));
         let fp = (hi_val << 128) + lo_val;
-        assert!(fp < modulus::<Fp>());
+        if fp >= modulus::<Fp>() {
+            return Err(Halo2WasmError::InvalidFieldElement(format!(
+                "`{fp}` is not less than the field modulus"
+            )));
+        }
         let fp = biguint_to_fe::<Fp>(&fp);
-        let mut builder = self.builder.borrow_mut();
-        let ctx = builder.main(0);
+        let mut ctx = self.ctx();
+        let ctx = &mut *ctx;
         let fp = fp_chip.load_private(ctx, fp);
         // constrain fq actually equals hi << 128 + lo
         constrain_limbs_equality(ctx, &self.range, [hi, lo], fp.limbs(), fp_chip.limb_bits());
-        fp
+        Ok(fp)
     }
     /// Doesn't range check limbs of g1_point
     fn load_bn254_g1_impl(
         &self,
         g1_chip: &EccChip<Fr, Bn254FqChip<Fr>>,
         point: JsCircuitBn254G1Affine,
-    ) -> Bn254G1AffinePoint {
-        let [x, y] = [point.x, point.y]
-            .map(|c| self.load_generic_fp_impl::<Bn254Fq>(g1_chip.field_chip(), c));
+    ) -> Result<Bn254G1AffinePoint, Halo2WasmError> {
+        let x = self.load_generic_fp_impl::<Bn254Fq>(g1_chip.field_chip(), point.x)?;
+        let y = self.load_generic_fp_impl::<Bn254Fq>(g1_chip.field_chip(), point.y)?;
         let pt = EcPoint::new(x, y);
-        g1_chip.assert_is_on_curve::<Bn254G1Affine>(self.builder.borrow_mut().main(0), &pt);
-        Bn254G1AffinePoint(pt)
+        g1_chip.assert_is_on_curve::<Bn254G1Affine>(&mut self.ctx(), &pt);
+        Ok(Bn254G1AffinePoint(pt))
     }
     /// Doesn't range check limbs of g2_point
     fn load_bn254_g2_impl(
         &self,
         g2_chip: &EccChip<Fr, Bn254Fq2Chip<Fr>>,
         point: JsCircuitBn254G2Affine,
-    ) -> Bn254G2AffinePoint {
+    ) -> Result<Bn254G2AffinePoint, Halo2WasmError> {
         let fq_chip = g2_chip.field_chip().fp_chip();
-        let [x, y] = [point.x, point.y].map(|c| {
-            let c0 = self.load_generic_fp_impl::<Bn254Fq>(fq_chip, c.c0);
-            let c1 = self.load_generic_fp_impl::<Bn254Fq>(fq_chip, c.c1);
-            FieldVector(vec![c0, c1])
-        });
-        let pt = EcPoint::new(x, y);
-        g2_chip.assert_is_on_curve::<Bn254G2Affine>(self.builder.borrow_mut().main(0), &pt);
-        Bn254G2AffinePoint(pt)
+        let load_fq2 = |c: JsCircuitBn254Fq2| -> Result<_, Halo2WasmError> {
+            let c0 = self.load_generic_fp_impl::<Bn254Fq>(fq_chip, c.c0)?;
+            let c1 = self.load_generic_fp_impl::<Bn254Fq>(fq_chip, c.c1)?;
+            Ok(FieldVector(vec![c0, c1]))
+        };
+        let pt = EcPoint::new(load_fq2(point.x)?, load_fq2(point.y)?);
+        g2_chip.assert_is_on_curve::<Bn254G2Affine>(&mut self.ctx(), &pt);
+        Ok(Bn254G2AffinePoint(pt))
     }
 }
 
@@ -525,10 +604,10 @@ fn convert_3limbs88bits_to_hi_lo(
     assert_eq!(limbs.len(), 3);
     let lo_bits = 128 - 88;
     let hi_bits = 88 - lo_bits;
-    let mut builder = lib_wasm.builder.borrow_mut();
     let range = &lib_wasm.range;
     let gate = &range.gate;
-    let ctx = builder.main(0);
+    let mut ctx = lib_wasm.ctx();
+    let ctx = &mut *ctx;
     let (limb1_hi, limb1_lo) = range.div_mod(ctx, limbs[1], BigUint::one() << lo_bits, 88);
     let multiplier = biguint_to_fe(&(BigUint::one() << 88));
     let lo = gate.mul_add(ctx, limb1_lo, Constant(multiplier), limbs[0]);
@@ -0,0 +1,128 @@
+//! Value handles passed to and from JS.
+//!
+//! A handle is the index of a cell in a table kept per circuit, with a tag in the bits above it.
+//! Every circuit takes a new tag whenever it is created or cleared, so a handle of another
+//! circuit, or of this one before it was cleared, is rejected instead of resolving to another
+//! cell. Tags wrap around after `NUM_TAGS` circuits, so only handles that old can alias.
+use std::cell::Cell;
+
+use halo2_base::{AssignedValue, Context};
+
+use super::{batch::BATCH_OUTPUT_FLAG, Fr};
+use crate::Halo2WasmError;
+
+/// Bits of a handle holding the index of its cell.
+const INDEX_BITS: u32 = 24;
+/// Maximum number of handles of a circuit.
+pub(crate) const MAX_HANDLES: usize = 1 << INDEX_BITS;
+/// Number of distinct tags, so that handles stay below `BATCH_OUTPUT_FLAG`, which marks the
+/// outputs of a batch instead.
+const NUM_TAGS: u32 = BATCH_OUTPUT_FLAG >> INDEX_BITS;
+
+thread_local! {
+    static NEXT_TAG: Cell<u32> = Cell::new(0);
+}
+
+fn next_tag() -> u32 {
+    NEXT_TAG.with(|next| {
+        let tag = next.get();
+        next.set((tag + 1) % NUM_TAGS);
+        tag
+    })
+}
+
+/// Returns the handle of the cell at `index` of the table of a circuit with tag `tag`.
+pub(crate) fn encode_handle(tag: u32, index: usize) -> Result<usize, Halo2WasmError> {
+    if index >= MAX_HANDLES {
+        return Err(Halo2WasmError::TooManyCells(MAX_HANDLES));
+    }
+    Ok(((tag as usize) << INDEX_BITS) | index)
+}
+
+/// Handles of the cells of a circuit passed to JS.
+#[derive(Debug)]
+pub(crate) struct Handles {
+    /// Number of times the circuit was cleared.
+    generation: u64,
+    tag: u32,
+    /// Thread and offset of the cell of each handle, by index.
+    cells: Vec<(usize, usize)>,
+    /// Index of the handle of each cell, by thread and offset.
+    indices: Vec<Vec<Option<u32>>>,
+}
+
+impl Default for Handles {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            tag: next_tag(),
+            cells: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+}
+
+impl Handles {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Forgets all handles, once the circuit is cleared.
+    pub fn clear(&mut self) {
+        self.generation += 1;
+        self.tag = next_tag();
+        self.cells.clear();
+        self.indices.clear();
+    }
+
+    /// Returns the handle of the cell at `offset` in `thread`, taking a new one on first use.
+    pub fn handle(&mut self, thread: usize, offset: usize) -> Result<usize, Halo2WasmError> {
+        if self.indices.len() <= thread {
+            self.indices.resize_with(thread + 1, Vec::new);
+        }
+        let indices = &mut self.indices[thread];
+        if indices.len() <= offset {
+            indices.resize(offset + 1, None);
+        }
+        if let Some(index) = indices[offset] {
+            return encode_handle(self.tag, index as usize);
+        }
+        let index = self.cells.len();
+        let handle = encode_handle(self.tag, index)?;
+        indices[offset] = Some(index as u32);
+        self.cells.push((thread, offset));
+        Ok(handle)
+    }
+
+    /// Returns the thread and offset of the cell referred to by `handle`.
+    pub fn cell(&self, handle: usize) -> Result<(usize, usize), Halo2WasmError> {
+        let index = handle & (MAX_HANDLES - 1);
+        if handle >> INDEX_BITS != self.tag as usize {
+            return Err(Halo2WasmError::StaleHandle(handle));
+        }
+        self.cells
+            .get(index)
+            .copied()
+            .ok_or(Halo2WasmError::StaleHandle(handle))
+    }
+
+    /// Returns the cell of `threads` referred to by `handle`.
+    pub fn lookup(
+        &self,
+        threads: &[Context<Fr>],
+        handle: usize,
+    ) -> Result<AssignedValue<Fr>, Halo2WasmError> {
+        let (thread, offset) = self.cell(handle)?;
+        let ctx = threads
+            .get(thread)
+            .ok_or(Halo2WasmError::ThreadOutOfRange {
+                thread,
+                num_threads: threads.len(),
+            })?;
+        let num_cells = ctx.advice.len();
+        if offset >= num_cells {
+            return Err(Halo2WasmError::CellOutOfRange { offset, num_cells });
+        }
+        Ok(ctx.get(offset as isize))
+    }
+}
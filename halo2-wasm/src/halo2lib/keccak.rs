@@ -0,0 +1,248 @@
+//! Keccak-256 over bytes, built from `GateChip` gates on the 1600 bits of the state as
+//! `halo2-base` has no keccak chip.
+//!
+//! This is a deliberate stopgap until the keccak chip of `zkevm-hashes` can be used here. Every
+//! bit operation is a gate, so a permutation, run once per 136 byte block, costs around 270k
+//! gates or 1.08M advice cells, more than a full advice column at k = 20. It is meant for short
+//! inputs such as public keys and message hashes.
+use halo2_base::{
+    gates::{GateChip, GateInstructions, RangeInstructions},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+use wasm_bindgen::prelude::*;
+
+use super::{
+    bits::{bits_to_num, xor},
+    ecc::JsCircuitValue256,
+    Fr, Halo2LibWasm,
+};
+use crate::Halo2WasmError;
+
+/// Bytes absorbed per permutation.
+const RATE_BYTES: usize = 136;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation of lane `(x, y)` in the rho step, indexed by `x` then `y`.
+const RHO_OFFSETS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+/// Index in the state of bit `z` of lane `(x, y)`. Lanes are little-endian, so the `i`-th bit of
+/// a block, with bytes in order and bits of each byte from the least significant, is bit `i`.
+fn bit(x: usize, y: usize, z: usize) -> usize {
+    64 * (x % 5 + 5 * (y % 5)) + z % 64
+}
+
+/// Applies keccak-f[1600] to the bits of `state`.
+fn keccak_f(ctx: &mut Context<Fr>, gate: &GateChip<Fr>, state: &mut [AssignedValue<Fr>]) {
+    for rc in ROUND_CONSTANTS {
+        // theta
+        let parity = (0..5)
+            .map(|x| {
+                (0..64)
+                    .map(|z| {
+                        (1..5).fold(state[bit(x, 0, z)], |acc, y| {
+                            xor(ctx, gate, acc, state[bit(x, y, z)])
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for x in 0..5 {
+            for z in 0..64 {
+                let d = xor(
+                    ctx,
+                    gate,
+                    parity[(x + 4) % 5][z],
+                    parity[(x + 1) % 5][(z + 63) % 64],
+                );
+                for y in 0..5 {
+                    state[bit(x, y, z)] = xor(ctx, gate, state[bit(x, y, z)], d);
+                }
+            }
+        }
+        // rho and pi only move bits
+        let mut b = state.to_vec();
+        for (x, offsets) in RHO_OFFSETS.iter().enumerate() {
+            for (y, offset) in offsets.iter().enumerate() {
+                for z in 0..64 {
+                    b[bit(y, 2 * x + 3 * y, z + offset)] = state[bit(x, y, z)];
+                }
+            }
+        }
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..64 {
+                    let t = gate.mul_not(ctx, b[bit(x + 1, y, z)], b[bit(x + 2, y, z)]);
+                    state[bit(x, y, z)] = xor(ctx, gate, b[bit(x, y, z)], t);
+                }
+            }
+        }
+        // iota
+        for z in (0..64).filter(|z| (rc >> z) & 1 == 1) {
+            state[bit(0, 0, z)] = gate.not(ctx, state[bit(0, 0, z)]);
+        }
+    }
+}
+
+/// Absorbs the bits of the padded message, a multiple of the rate, returning the state after
+/// each block.
+fn absorb(
+    ctx: &mut Context<Fr>,
+    gate: &GateChip<Fr>,
+    padded: &[AssignedValue<Fr>],
+) -> Vec<Vec<AssignedValue<Fr>>> {
+    let mut state = vec![ctx.load_zero(); 1600];
+    padded
+        .chunks(8 * RATE_BYTES)
+        .map(|block| {
+            for (i, bit) in block.iter().enumerate() {
+                state[i] = xor(ctx, gate, state[i], *bit);
+            }
+            keccak_f(ctx, gate, &mut state);
+            state.clone()
+        })
+        .collect()
+}
+
+/// Returns the first 32 bytes of `state` as a big-endian hi-lo pair.
+fn squeeze(
+    ctx: &mut Context<Fr>,
+    gate: &GateChip<Fr>,
+    state: &[AssignedValue<Fr>],
+) -> [AssignedValue<Fr>; 2] {
+    [0, 16].map(|start| {
+        let bits = (start..start + 16)
+            .rev()
+            .flat_map(|byte| state[8 * byte..8 * byte + 8].iter().copied())
+            .collect::<Vec<_>>();
+        bits_to_num(ctx, gate, &bits)
+    })
+}
+
+#[wasm_bindgen]
+impl Halo2LibWasm {
+    /// Returns the keccak256 hash of the bytes `a` as a big-endian hi-lo pair, as in Ethereum.
+    ///
+    /// Every value of `a` is constrained to be a byte.
+    pub fn keccak256(&mut self, a: &[u32]) -> Result<JsCircuitValue256, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let [hi, lo] = {
+            let ctx = &mut *self.ctx();
+            let gate = &self.gate;
+            let mut padding = vec![0u8; RATE_BYTES - a.len() % RATE_BYTES];
+            padding[0] |= 0x01;
+            *padding.last_mut().unwrap() |= 0x80;
+            let mut padded = a
+                .into_iter()
+                .flat_map(|byte| gate.num_to_bits(ctx, byte, 8))
+                .collect::<Vec<_>>();
+            for byte in padding {
+                for i in 0..8 {
+                    padded.push(ctx.load_constant(Fr::from(((byte >> i) & 1) as u64)));
+                }
+            }
+            let states = absorb(ctx, gate, &padded);
+            squeeze(ctx, gate, states.last().unwrap())
+        };
+        let out = JsCircuitValue256::new(
+            self.to_js_assigned_value(hi)?,
+            self.to_js_assigned_value(lo)?,
+        );
+        self.trace_op("keccak256", start, Ok(out))
+    }
+
+    /// Returns the keccak256 hash of the first `len` of the bytes `a` as a big-endian hi-lo
+    /// pair, where `len` is a value at most the number of bytes.
+    ///
+    /// Every value of `a` is constrained to be a byte. The cost is that of hashing all of `a`.
+    #[wasm_bindgen(js_name = keccak256VarLen)]
+    pub fn keccak256_var_len(
+        &mut self,
+        a: &[u32],
+        len: usize,
+    ) -> Result<JsCircuitValue256, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let len = self.assigned_value(len)?;
+        let [hi, lo] = {
+            let ctx = &mut *self.ctx();
+            let (gate, range) = (&self.gate, &self.range);
+            let max_len = a.len();
+            let num_bytes = (max_len / RATE_BYTES + 1) * RATE_BYTES;
+            let len_bits = usize::BITS as usize - num_bytes.leading_zeros() as usize;
+            range.check_less_than_safe(ctx, len, max_len as u64 + 1);
+            for byte in &a {
+                range.range_check(ctx, *byte, 8);
+            }
+            // the 0x01 padding byte is at `len` and the 0x80 one at the end of its block
+            let (last_block, _) = range.div_mod(ctx, len, RATE_BYTES, len_bits);
+            let last_byte = gate.mul_add(
+                ctx,
+                last_block,
+                Constant(Fr::from(RATE_BYTES as u64)),
+                Constant(Fr::from(RATE_BYTES as u64 - 1)),
+            );
+            let is_len = gate.idx_to_indicator(ctx, len, num_bytes);
+            let is_last = gate.idx_to_indicator(ctx, last_byte, num_bytes);
+            let zero = ctx.load_zero();
+            let mut past_len = zero;
+            let mut padded = Vec::with_capacity(8 * num_bytes);
+            for (i, (is_len, is_last)) in is_len.into_iter().zip(is_last).enumerate() {
+                past_len = gate.add(ctx, past_len, is_len);
+                let byte = gate.mul_not(ctx, past_len, a.get(i).copied().unwrap_or(zero));
+                let byte = gate.add(ctx, byte, is_len);
+                let byte = gate.mul_add(ctx, is_last, Constant(Fr::from(0x80)), byte);
+                padded.extend(gate.num_to_bits(ctx, byte, 8));
+            }
+            let states = absorb(ctx, gate, &padded);
+            let [his, los]: [Vec<_>; 2] = states
+                .iter()
+                .map(|state| squeeze(ctx, gate, state))
+                .fold([vec![], vec![]], |[mut his, mut los], [hi, lo]| {
+                    his.push(hi);
+                    los.push(lo);
+                    [his, los]
+                });
+            [his, los].map(|x| gate.select_from_idx(ctx, x, last_block))
+        };
+        let out = JsCircuitValue256::new(
+            self.to_js_assigned_value(hi)?,
+            self.to_js_assigned_value(lo)?,
+        );
+        self.trace_op("keccak256_var_len", start, Ok(out))
+    }
+}
@@ -0,0 +1,183 @@
+//! Poseidon Merkle inclusion proofs, in circuit and with a native tree to produce the paths.
+//!
+//! A parent is the Poseidon hash of its left and right children. Paths are given from the leaf
+//! up, with path bit `i` set if the node at height `i` is a right child.
+use halo2_base::{gates::GateInstructions, AssignedValue};
+use wasm_bindgen::prelude::*;
+
+use super::{
+    poseidon::{native_hasher, PoseidonSpec},
+    Fr, Halo2LibWasm,
+};
+use crate::{
+    utils::{fr_from_str, fr_to_hex},
+    Halo2WasmError,
+};
+
+#[wasm_bindgen]
+impl Halo2LibWasm {
+    /// Computes the root of the Merkle tree containing `leaf` from the `siblings` and
+    /// `path_bits` of its path, hashed with Poseidon of the given spec, `T3` if unset.
+    ///
+    /// Every path bit is constrained to be 0 or 1.
+    #[wasm_bindgen(js_name = merkleRootFromPath)]
+    pub fn merkle_root_from_path(
+        &mut self,
+        leaf: usize,
+        siblings: &[u32],
+        path_bits: &[u32],
+        spec: Option<PoseidonSpec>,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let root = self.merkle_root(leaf, siblings, path_bits, spec)?;
+        self.trace_op(
+            "merkle_root_from_path",
+            start,
+            self.to_js_assigned_value(root),
+        )
+    }
+
+    /// Constrains `leaf` to be in the Merkle tree with the given `root`, see
+    /// `merkleRootFromPath`.
+    #[wasm_bindgen(js_name = merkleVerify)]
+    pub fn merkle_verify(
+        &mut self,
+        root: usize,
+        leaf: usize,
+        siblings: &[u32],
+        path_bits: &[u32],
+        spec: Option<PoseidonSpec>,
+    ) -> Result<(), Halo2WasmError> {
+        let start = self.trace_start();
+        let root = self.assigned_value(root)?;
+        let computed = self.merkle_root(leaf, siblings, path_bits, spec)?;
+        self.ctx().constrain_equal(&root, &computed);
+        self.trace_op("merkle_verify", start, Ok(()))
+    }
+}
+
+impl Halo2LibWasm {
+    fn merkle_root(
+        &self,
+        leaf: usize,
+        siblings: &[u32],
+        path_bits: &[u32],
+        spec: Option<PoseidonSpec>,
+    ) -> Result<AssignedValue<Fr>, Halo2WasmError> {
+        if siblings.len() != path_bits.len() {
+            return Err(Halo2WasmError::MerklePathMismatch {
+                siblings: siblings.len(),
+                path_bits: path_bits.len(),
+            });
+        }
+        let siblings = self.get_assigned_values(siblings)?;
+        let path_bits = self.get_assigned_values(path_bits)?;
+        let mut node = self.assigned_value(leaf)?;
+        for (sibling, bit) in siblings.into_iter().zip(path_bits) {
+            let children = {
+                let ctx = &mut *self.ctx();
+                self.gate.assert_bit(ctx, bit);
+                [
+                    self.gate.select(ctx, sibling, node, bit),
+                    self.gate.select(ctx, node, sibling, bit),
+                ]
+            };
+            node = self.poseidon_hash(&children, None, spec);
+        }
+        Ok(node)
+    }
+}
+
+/// A Poseidon Merkle tree computed natively, with roots and paths matching
+/// `Halo2LibWasm.merkleRootFromPath`.
+///
+/// The leaves are padded with zeros to a power of two.
+#[wasm_bindgen]
+pub struct MerkleTree {
+    /// Levels of the tree from the padded leaves up to the root.
+    layers: Vec<Vec<Fr>>,
+    num_leaves: usize,
+}
+
+#[wasm_bindgen]
+impl MerkleTree {
+    /// Builds the tree of `leaves`, given as decimal or `0x`-prefixed hex strings, hashed with
+    /// Poseidon of the given spec, `T3` if unset.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        leaves: Vec<String>,
+        spec: Option<PoseidonSpec>,
+    ) -> Result<MerkleTree, Halo2WasmError> {
+        let leaves = leaves
+            .iter()
+            .map(|x| fr_from_str(x))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_leaves(leaves, spec)
+    }
+
+    /// Returns the root as a `0x`-prefixed big-endian hex string.
+    pub fn root(&self) -> String {
+        fr_to_hex(&self.root_value())
+    }
+
+    /// Number of levels above the leaves, which is the length of every path.
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    /// Returns the siblings on the path of leaf `index` as `0x`-prefixed big-endian hex strings.
+    pub fn siblings(&self, index: usize) -> Result<Vec<String>, Halo2WasmError> {
+        let (siblings, _) = self.path(index)?;
+        Ok(siblings.iter().map(fr_to_hex).collect())
+    }
+
+    /// Returns the path bits of leaf `index`.
+    #[wasm_bindgen(js_name = pathBits)]
+    pub fn path_bits(&self, index: usize) -> Result<Vec<u32>, Halo2WasmError> {
+        let (_, path_bits) = self.path(index)?;
+        Ok(path_bits)
+    }
+}
+
+impl MerkleTree {
+    /// Native version of the `MerkleTree` constructor.
+    pub fn from_leaves(
+        mut leaves: Vec<Fr>,
+        spec: Option<PoseidonSpec>,
+    ) -> Result<Self, Halo2WasmError> {
+        if leaves.is_empty() {
+            return Err(Halo2WasmError::NoLeaves);
+        }
+        let num_leaves = leaves.len();
+        leaves.resize(num_leaves.next_power_of_two(), Fr::from(0));
+        let hash = native_hasher(spec.unwrap_or(PoseidonSpec::T3));
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let parents = layer.chunks(2).map(&hash).collect();
+            layers.push(parents);
+        }
+        Ok(Self { layers, num_leaves })
+    }
+
+    pub fn root_value(&self) -> Fr {
+        self.layers[self.depth()][0]
+    }
+
+    /// Returns the siblings and path bits of leaf `index`, from the leaf up.
+    pub fn path(&self, index: usize) -> Result<(Vec<Fr>, Vec<u32>), Halo2WasmError> {
+        if index >= self.num_leaves {
+            return Err(Halo2WasmError::LeafOutOfRange {
+                index,
+                num_leaves: self.num_leaves,
+            });
+        }
+        Ok(self.layers[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, layer)| {
+                let i = index >> height;
+                (layer[i ^ 1], (i & 1) as u32)
+            })
+            .unzip())
+    }
+}
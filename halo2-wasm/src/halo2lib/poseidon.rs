@@ -0,0 +1,174 @@
+//! Poseidon hashing with a choice of width, reusing one hasher per spec and circuit, and the
+//! same hash computed natively.
+use halo2_base::{
+    gates::range::{RangeChip, RangeInstructions},
+    poseidon::hasher::{spec::OptimizedPoseidonSpec, PoseidonHasher},
+    AssignedValue, Context,
+};
+use snark_verifier::{loader::native::NativeLoader, util::hash::Poseidon};
+use wasm_bindgen::prelude::*;
+
+use super::{Fr, Halo2LibWasm, R_F, SECURE_MDS};
+use crate::{
+    utils::{fr_from_str, fr_to_hex},
+    Halo2WasmError,
+};
+
+/// Width of a Poseidon hash over BN254, with 8 full rounds and the number of partial rounds
+/// recommended for that width. The rate is one less than the width.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoseidonSpec {
+    /// Width 3, 57 partial rounds. The default.
+    T3 = 0,
+    /// Width 4, 56 partial rounds.
+    T4 = 1,
+    /// Width 5, 60 partial rounds, as in the snark-verifier transcript.
+    T5 = 2,
+    /// Width 9, 63 partial rounds.
+    T9 = 3,
+}
+
+/// Hashers of each spec, created and their constants assigned on first use in a circuit.
+#[derive(Default)]
+pub(super) struct PoseidonHashers {
+    /// Generation of the circuit the constants are assigned in.
+    generation: u64,
+    t3: Option<PoseidonHasher<Fr, 3, 2>>,
+    t4: Option<PoseidonHasher<Fr, 4, 3>>,
+    t5: Option<PoseidonHasher<Fr, 5, 4>>,
+    t9: Option<PoseidonHasher<Fr, 9, 8>>,
+}
+
+impl PoseidonHashers {
+    /// Hashes `inputs`, or its first `len` elements if given.
+    fn hash(
+        &mut self,
+        generation: u64,
+        spec: PoseidonSpec,
+        ctx: &mut Context<Fr>,
+        range: &RangeChip<Fr>,
+        inputs: &[AssignedValue<Fr>],
+        len: Option<AssignedValue<Fr>>,
+    ) -> AssignedValue<Fr> {
+        if self.generation != generation {
+            *self = Self {
+                generation,
+                ..Default::default()
+            };
+        }
+        match spec {
+            PoseidonSpec::T3 => hash::<3, 2, 57>(&mut self.t3, ctx, range, inputs, len),
+            PoseidonSpec::T4 => hash::<4, 3, 56>(&mut self.t4, ctx, range, inputs, len),
+            PoseidonSpec::T5 => hash::<5, 4, 60>(&mut self.t5, ctx, range, inputs, len),
+            PoseidonSpec::T9 => hash::<9, 8, 63>(&mut self.t9, ctx, range, inputs, len),
+        }
+    }
+}
+
+fn hash<const T: usize, const RATE: usize, const R_P: usize>(
+    hasher: &mut Option<PoseidonHasher<Fr, T, RATE>>,
+    ctx: &mut Context<Fr>,
+    range: &RangeChip<Fr>,
+    inputs: &[AssignedValue<Fr>],
+    len: Option<AssignedValue<Fr>>,
+) -> AssignedValue<Fr> {
+    let hasher = hasher.get_or_insert_with(|| {
+        let spec = OptimizedPoseidonSpec::new::<R_F, R_P, SECURE_MDS>();
+        let mut hasher = PoseidonHasher::new(spec);
+        hasher.initialize_consts(ctx, range.gate());
+        hasher
+    });
+    match len {
+        Some(len) => hasher.hash_var_len_array(ctx, range, inputs, len),
+        None => hasher.hash_fix_len_array(ctx, range.gate(), inputs),
+    }
+}
+
+/// Hashes the field elements `inputs`, given as decimal or `0x`-prefixed hex strings, with
+/// Poseidon of the given spec, `T3` if unset. Returns the same value as `Halo2LibWasm.poseidon`
+/// as a `0x`-prefixed big-endian hex string.
+#[wasm_bindgen(js_name = poseidonNative)]
+pub fn poseidon_native(
+    inputs: Vec<String>,
+    spec: Option<PoseidonSpec>,
+) -> Result<String, Halo2WasmError> {
+    let inputs = inputs
+        .iter()
+        .map(|x| fr_from_str(x))
+        .collect::<Result<Vec<_>, _>>()?;
+    let hash = native_hasher(spec.unwrap_or(PoseidonSpec::T3));
+    Ok(fr_to_hex(&hash(&inputs)))
+}
+
+/// Returns a native Poseidon hash of the given spec, computing the same values as the gadget.
+/// The round constants are generated once and reused by every call.
+pub fn native_hasher(spec: PoseidonSpec) -> Box<dyn Fn(&[Fr]) -> Fr> {
+    match spec {
+        PoseidonSpec::T3 => native::<3, 2, 57>(),
+        PoseidonSpec::T4 => native::<4, 3, 56>(),
+        PoseidonSpec::T5 => native::<5, 4, 60>(),
+        PoseidonSpec::T9 => native::<9, 8, 63>(),
+    }
+}
+
+fn native<const T: usize, const RATE: usize, const R_P: usize>() -> Box<dyn Fn(&[Fr]) -> Fr> {
+    let spec = OptimizedPoseidonSpec::<Fr, T, RATE>::new::<R_F, R_P, SECURE_MDS>();
+    Box::new(move |inputs| {
+        let mut sponge = Poseidon::<Fr, Fr, T, RATE>::from_spec(&NativeLoader, spec.clone());
+        sponge.update(inputs);
+        sponge.squeeze()
+    })
+}
+
+#[wasm_bindgen]
+impl Halo2LibWasm {
+    /// Hashes the values `a` with Poseidon of the given spec, `T3` if unset.
+    pub fn poseidon(
+        &mut self,
+        a: &[u32],
+        spec: Option<PoseidonSpec>,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let out = self.poseidon_hash(&a, None, spec);
+        self.trace_op("poseidon", start, self.to_js_assigned_value(out))
+    }
+
+    /// Hashes the first `len` of the values `a` with Poseidon of the given spec, `T3` if unset.
+    ///
+    /// `len` is a value, so the number of elements hashed is part of the witness. It is
+    /// constrained to be at most the number of values.
+    #[wasm_bindgen(js_name = poseidonVarLen)]
+    pub fn poseidon_var_len(
+        &mut self,
+        a: &[u32],
+        len: usize,
+        spec: Option<PoseidonSpec>,
+    ) -> Result<usize, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let len = self.assigned_value(len)?;
+        let out = self.poseidon_hash(&a, Some(len), spec);
+        self.trace_op("poseidon_var_len", start, self.to_js_assigned_value(out))
+    }
+}
+
+impl Halo2LibWasm {
+    /// Hashes `inputs`, or its first `len` elements if given, with the cached hasher of `spec`.
+    pub(super) fn poseidon_hash(
+        &self,
+        inputs: &[AssignedValue<Fr>],
+        len: Option<AssignedValue<Fr>>,
+        spec: Option<PoseidonSpec>,
+    ) -> AssignedValue<Fr> {
+        self.poseidon.borrow_mut().hash(
+            self.handles.borrow().generation(),
+            spec.unwrap_or(PoseidonSpec::T3),
+            &mut self.ctx(),
+            &self.range,
+            inputs,
+            len,
+        )
+    }
+}
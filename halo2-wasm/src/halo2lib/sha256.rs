@@ -0,0 +1,190 @@
+//! SHA-256 over bytes, built from `GateChip` gates on the bits of 32-bit words as `halo2-base`
+//! has no SHA-256 chip.
+//!
+//! Additions are done on the recomposed words, so a compression, run once per 64 byte block,
+//! costs around 80k gates.
+use halo2_base::{
+    gates::{GateChip, GateInstructions},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+use wasm_bindgen::prelude::*;
+
+use super::{
+    bits::{bits_to_num, xor},
+    ecc::JsCircuitValue256,
+    Fr, Halo2LibWasm,
+};
+use crate::Halo2WasmError;
+
+pub(crate) const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub(crate) const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Bits of a 32-bit word, from the least significant.
+type Word = Vec<AssignedValue<Fr>>;
+
+fn rotr(x: &[AssignedValue<Fr>], n: usize) -> Word {
+    (0..32).map(|i| x[(i + n) % 32]).collect()
+}
+
+fn shr(x: &[AssignedValue<Fr>], n: usize, zero: AssignedValue<Fr>) -> Word {
+    (0..32)
+        .map(|i| x.get(i + n).copied().unwrap_or(zero))
+        .collect()
+}
+
+fn xor3(ctx: &mut Context<Fr>, gate: &GateChip<Fr>, [a, b, c]: [Word; 3]) -> Word {
+    (0..32)
+        .map(|i| {
+            let t = xor(ctx, gate, a[i], b[i]);
+            xor(ctx, gate, t, c[i])
+        })
+        .collect()
+}
+
+/// Returns the sum of `words` and `constant` modulo 2^32.
+fn add(
+    ctx: &mut Context<Fr>,
+    gate: &GateChip<Fr>,
+    words: &[&[AssignedValue<Fr>]],
+    constant: u32,
+) -> Word {
+    let bits = words.iter().flat_map(|word| word.iter().copied());
+    let powers = words
+        .iter()
+        .flat_map(|_| gate.pow_of_two()[..32].iter().map(|x| Constant(*x)));
+    let sum = gate.inner_product(ctx, bits, powers);
+    let sum = gate.add(ctx, sum, Constant(Fr::from(constant as u64)));
+    // the `words.len() + 1` terms are each less than 2^32, so the carry fits in the bit length
+    // of `words.len()`
+    let carry_bits = (usize::BITS - words.len().leading_zeros()) as usize;
+    let mut bits = gate.num_to_bits(ctx, sum, 32 + carry_bits);
+    bits.truncate(32);
+    bits
+}
+
+/// Applies the compression function to `state` and a block of 16 words.
+fn compress(
+    ctx: &mut Context<Fr>,
+    gate: &GateChip<Fr>,
+    state: &[Word],
+    block: &[Word],
+    zero: AssignedValue<Fr>,
+) -> Vec<Word> {
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = xor3(
+            ctx,
+            gate,
+            [
+                rotr(&w[t - 15], 7),
+                rotr(&w[t - 15], 18),
+                shr(&w[t - 15], 3, zero),
+            ],
+        );
+        let s1 = xor3(
+            ctx,
+            gate,
+            [
+                rotr(&w[t - 2], 17),
+                rotr(&w[t - 2], 19),
+                shr(&w[t - 2], 10, zero),
+            ],
+        );
+        let next = add(ctx, gate, &[&s1, &w[t - 7], &s0, &w[t - 16]], 0);
+        w.push(next);
+    }
+    let mut v = state.to_vec();
+    for (w, k) in w.iter().zip(ROUND_CONSTANTS) {
+        let [a, b, c, d, e, f, g, h] = <[Word; 8]>::try_from(v).unwrap();
+        let s1 = xor3(ctx, gate, [rotr(&e, 6), rotr(&e, 11), rotr(&e, 25)]);
+        let ch = (0..32)
+            .map(|i| gate.select(ctx, f[i], g[i], e[i]))
+            .collect::<Word>();
+        let s0 = xor3(ctx, gate, [rotr(&a, 2), rotr(&a, 13), rotr(&a, 22)]);
+        // the majority is `c` where `a` and `b` differ, and `a` otherwise
+        let maj = (0..32)
+            .map(|i| {
+                let t = xor(ctx, gate, a[i], b[i]);
+                gate.select(ctx, c[i], a[i], t)
+            })
+            .collect::<Word>();
+        let next_e = add(ctx, gate, &[&d, &h, &s1, &ch, w], k);
+        let next_a = add(ctx, gate, &[&h, &s1, &ch, w, &s0, &maj], k);
+        v = vec![next_a, a, b, c, next_e, e, f, g];
+    }
+    state
+        .iter()
+        .zip(v)
+        .map(|(x, y)| add(ctx, gate, &[x, &y], 0))
+        .collect()
+}
+
+#[wasm_bindgen]
+impl Halo2LibWasm {
+    /// Returns the SHA-256 hash of the bytes `a` as a big-endian hi-lo pair.
+    ///
+    /// Every value of `a` is constrained to be a byte.
+    pub fn sha256(&mut self, a: &[u32]) -> Result<JsCircuitValue256, Halo2WasmError> {
+        let start = self.trace_start();
+        let a = self.get_assigned_values(a)?;
+        let [hi, lo] = {
+            let ctx = &mut *self.ctx();
+            let gate = &self.gate;
+            let [zero, one] = [0u64, 1].map(|x| ctx.load_constant(Fr::from(x)));
+            let constant_bits = |x: u64, num_bits: usize| -> Word {
+                (0..num_bits)
+                    .map(|i| if (x >> i) & 1 == 1 { one } else { zero })
+                    .collect()
+            };
+            // 0x80, zeros, and the length in bits as a big-endian u64 fill up the last block
+            let num_bytes = ((a.len() + 8) / 64 + 1) * 64;
+            let mut padding = vec![0u8; num_bytes - a.len()];
+            padding[0] = 0x80;
+            let len = padding.len();
+            padding[len - 8..].copy_from_slice(&(8 * a.len() as u64).to_be_bytes());
+            let bytes = a
+                .into_iter()
+                .map(|byte| gate.num_to_bits(ctx, byte, 8))
+                .chain(
+                    padding
+                        .into_iter()
+                        .map(|byte| constant_bits(byte as u64, 8)),
+                )
+                .collect::<Vec<_>>();
+            // words are big-endian
+            let words = bytes
+                .chunks(4)
+                .map(|word| word.iter().rev().flatten().copied().collect())
+                .collect::<Vec<Word>>();
+            let mut state = INITIAL_STATE
+                .iter()
+                .map(|x| constant_bits(*x as u64, 32))
+                .collect::<Vec<_>>();
+            for block in words.chunks(16) {
+                state = compress(ctx, gate, &state, block, zero);
+            }
+            [&state[..4], &state[4..]].map(|words| {
+                let bits = words.iter().rev().flatten().copied().collect::<Vec<_>>();
+                bits_to_num(ctx, gate, &bits)
+            })
+        };
+        let out = JsCircuitValue256::new(
+            self.to_js_assigned_value(hi)?,
+            self.to_js_assigned_value(lo)?,
+        );
+        self.trace_op("sha256", start, Ok(out))
+    }
+}
@@ -1,34 +1,38 @@
 #![feature(trait_alias)]
 
+#[cfg(feature = "prover")]
 use std::cell::RefCell;
+#[cfg(feature = "prover")]
 use std::io::BufReader;
+#[cfg(feature = "prover")]
 use std::rc::Rc;
 
+#[cfg(feature = "prover")]
 use halo2_base::{
-    gates::circuit::{builder::BaseCircuitBuilder, BaseCircuitParams},
+    gates::{
+        circuit::{builder::BaseCircuitBuilder, BaseCircuitParams},
+        flex_gate::MultiPhaseThreadBreakPoints,
+    },
     AssignedValue,
 };
+#[cfg(feature = "prover")]
 pub use halo2_ecc;
+#[cfg(feature = "prover")]
 use halo2_proofs::{
-    dev::MockProver,
+    dev::{MockProver, VerifyFailure},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
     plonk::*,
-    poly::{
-        commitment::{Params, ParamsProver},
-        kzg::{
-            commitment::{KZGCommitmentScheme, ParamsKZG},
-            multiopen::VerifierSHPLONK,
-            strategy::SingleStrategy,
-        },
-    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
 };
-use itertools::{concat, Itertools};
+#[cfg(feature = "prover")]
+use itertools::Itertools;
+#[cfg(feature = "prover")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "prover")]
 use snark_verifier::system::halo2::transcript_initial_state;
-use snark_verifier_sdk::{
-    halo2::{gen_snark_shplonk, PoseidonTranscript, POSEIDON_SPEC},
-    NativeLoader,
-};
+#[cfg(feature = "prover")]
+use snark_verifier_sdk::Snark;
+#[cfg(feature = "prover")]
 use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
@@ -38,14 +42,45 @@ pub use halo2_base::halo2_proofs;
 #[cfg(all(target_family = "wasm", feature = "rayon"))]
 pub use wasm_bindgen_rayon::init_thread_pool;
 
+#[cfg(feature = "prover")]
+pub mod aggregation;
+pub mod artifact;
+mod error;
+#[cfg(feature = "prover")]
+pub mod evm;
+#[cfg(feature = "prover")]
 pub mod halo2lib;
+#[cfg(feature = "prover")]
+pub mod mock;
+pub mod snark;
+pub mod transcript;
+pub mod utils;
+pub mod verifier;
+#[cfg(feature = "prover")]
 mod vkey;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 pub mod tests;
 
+#[cfg(feature = "prover")]
+use artifact::CircuitArtifact;
+pub use error::Halo2WasmError;
+#[cfg(feature = "prover")]
+use halo2lib::handles::Handles;
+#[cfg(feature = "prover")]
+use mock::{CellLocator, MockCheckResult, MockFailure};
+#[cfg(feature = "prover")]
+use snark::snark_to_bytes;
+#[cfg(feature = "prover")]
+use transcript::{gen_snark, TranscriptType};
+#[cfg(feature = "prover")]
+use utils::{encode_fr, fr_from_str, fr_to_hex, FieldEncoding};
+#[cfg(feature = "prover")]
+use verifier::{read_vk, verifier_params, verify_shplonk};
+#[cfg(feature = "prover")]
 use vkey::{write_partial_vkey, PartialVerifyingKey};
 
+#[cfg(feature = "prover")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -55,10 +90,12 @@ extern "C" {
     fn error(s: &str);
 }
 
+#[cfg(feature = "prover")]
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+#[cfg(feature = "prover")]
 #[derive(Tsify, Serialize, Deserialize)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub struct CircuitStats {
@@ -69,7 +106,12 @@ pub struct CircuitStats {
     k: usize,
 }
 
-#[derive(Tsify, Serialize, Deserialize)]
+/// Rows reserved for blinding factors when choosing a config in `Halo2Wasm::auto_config`.
+#[cfg(feature = "prover")]
+pub(crate) const MINIMUM_ROWS: usize = 20;
+
+#[cfg(feature = "prover")]
+#[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 pub struct CircuitConfig {
@@ -79,35 +121,57 @@ pub struct CircuitConfig {
     num_instance: usize,
     num_lookup_bits: usize,
     num_virtual_instance: usize,
+    /// Defaults to 1 if not set.
+    #[tsify(optional)]
+    #[serde(default)]
+    num_fixed: Option<usize>,
+    /// Instance column each virtual instance is assigned to, defaults to column 0 for all.
+    /// Virtual instances mapped to the same column are concatenated in order.
+    #[tsify(optional)]
+    #[serde(default)]
+    instance_columns: Option<Vec<usize>>,
 }
 
+#[cfg(feature = "prover")]
 #[wasm_bindgen]
 pub struct Halo2Wasm {
     #[wasm_bindgen(skip)]
     pub circuit: Rc<RefCell<BaseCircuitBuilder<Fr>>>,
+    /// Handles of the cells of `circuit` passed to JS, forgotten whenever it is reset.
+    pub(crate) handles: Rc<RefCell<Handles>>,
     #[wasm_bindgen(skip)]
     pub public: Vec<Vec<AssignedValue<Fr>>>,
+    /// Instance column of each virtual instance in `public`.
+    instance_columns: Vec<usize>,
     #[wasm_bindgen(skip)]
     pub circuit_params: Option<BaseCircuitParams>,
     params: Option<ParamsKZG<Bn256>>,
     pk: Option<ProvingKey<G1Affine>>,
     vk: Option<VerifyingKey<G1Affine>>,
+    break_points: Option<MultiPhaseThreadBreakPoints>,
+    transcript: TranscriptType,
 }
 
+#[cfg(feature = "prover")]
 impl Default for Halo2Wasm {
     fn default() -> Self {
         let circuit = BaseCircuitBuilder::new(false);
         Halo2Wasm {
             circuit: Rc::new(RefCell::new(circuit)),
+            handles: Rc::default(),
             public: vec![],
+            instance_columns: vec![],
             circuit_params: None,
             params: None,
             pk: None,
             vk: None,
+            break_points: None,
+            transcript: TranscriptType::default(),
         }
     }
 }
 
+#[cfg(feature = "prover")]
 #[wasm_bindgen]
 impl Halo2Wasm {
     #[wasm_bindgen(constructor)]
@@ -115,12 +179,11 @@ impl Halo2Wasm {
         Self::default()
     }
 
-    pub fn clear(&mut self) {
-        let circuit_params = self.circuit_params.clone().unwrap();
-        let circuit = BaseCircuitBuilder::new(false).use_params(circuit_params);
-        let circuit_cell = &self.circuit;
-        circuit_cell.replace(circuit);
-        self.clear_instances();
+    pub fn clear(&mut self) -> Result<(), Halo2WasmError> {
+        let circuit_params = self.circuit_params()?.clone();
+        let witness_gen_only = self.circuit.borrow().witness_gen_only();
+        self.reset_circuit(circuit_params, witness_gen_only);
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = clearInstances)]
@@ -131,117 +194,200 @@ impl Halo2Wasm {
             .collect();
     }
 
-    pub fn verify(&self, proof: &[u8]) {
-        let flattened: Vec<AssignedValue<Fr>> = concat(self.public.clone());
-        let instances = flattened.iter().map(|x| *x.value()).collect();
-        let instances = vec![instances];
-        let instances = instances.iter().map(Vec::as_slice).collect_vec();
-
-        let params = self.params.as_ref().unwrap();
-        let vk = self.vk.clone().unwrap();
-
-        let verifier_params = params.verifier_params();
-        let mut transcript_read =
-            PoseidonTranscript::<NativeLoader, &[u8]>::from_spec(proof, POSEIDON_SPEC.clone());
-
-        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
-            verifier_params,
-            &vk,
-            SingleStrategy::new(verifier_params),
-            &[&instances],
-            &mut transcript_read,
-        )
-        .unwrap();
+    pub fn verify(&self, proof: &[u8]) -> Result<(), Halo2WasmError> {
+        self.verify_instances(proof, &self.instance_values())?;
+        Ok(())
     }
 
-    #[wasm_bindgen(js_name = getInstances)]
-    pub fn get_instances(&mut self, col: usize) -> Vec<u32> {
-        let values: Vec<u32> = self
-            .public
-            .get(col)
-            .unwrap()
+    /// Verifies `proof` against explicit public inputs, one array of field element strings
+    /// (decimal or `0x`-prefixed hex) per instance column.
+    ///
+    /// Only requires params and a verifying key. Returns `false` if the proof is invalid.
+    #[wasm_bindgen(js_name = verifyProof)]
+    pub fn verify_proof(&self, proof: &[u8], instances: JsValue) -> Result<bool, Halo2WasmError> {
+        let instances: Vec<Vec<String>> = serde_wasm_bindgen::from_value(instances)
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        let instances = instances
             .iter()
-            .map(|x| x.cell.unwrap().offset as u32)
-            .collect();
-        values
+            .map(|col| col.iter().map(|x| fr_from_str(x)).collect())
+            .collect::<Result<Vec<Vec<Fr>>, _>>()?;
+        self.verify_proof_with_instances(proof, &instances)
     }
 
-    #[wasm_bindgen(js_name = setInstances)]
-    pub fn set_instances(&mut self, instances: &[u32], col: usize) {
-        let instances: Vec<AssignedValue<Fr>> = instances
+    /// Returns the handles of the values in virtual instance column `col`.
+    #[wasm_bindgen(js_name = getInstances)]
+    pub fn get_instances(&mut self, col: usize) -> Result<Vec<u32>, Halo2WasmError> {
+        let mut handles = self.handles.borrow_mut();
+        let values = self
+            .instance_column(col)?
             .iter()
             .map(|x| {
-                self.circuit
-                    .borrow_mut()
-                    .main(0)
-                    .get((*x).try_into().unwrap())
+                let cell = x.cell.unwrap();
+                Ok(handles.handle(cell.context_id, cell.offset)? as u32)
             })
-            .collect();
-        let public = self.public.get_mut(col).unwrap();
+            .collect::<Result<Vec<u32>, Halo2WasmError>>()?;
+        Ok(values)
+    }
+
+    /// Sets virtual instance column `col` to the values with the given handles.
+    #[wasm_bindgen(js_name = setInstances)]
+    pub fn set_instances(&mut self, instances: &[u32], col: usize) -> Result<(), Halo2WasmError> {
+        self.instance_column(col)?;
+        let instances = {
+            let mut circuit = self.circuit.borrow_mut();
+            let threads = &circuit.pool(0).threads;
+            let handles = self.handles.borrow();
+            instances
+                .iter()
+                .map(|x| handles.lookup(threads, *x as usize))
+                .collect::<Result<Vec<AssignedValue<Fr>>, _>>()?
+        };
+        let public = &mut self.public[col];
         public.clear();
         public.extend(instances);
+        Ok(())
     }
 
+    /// Returns the values of the instances in virtual column `col` as `0x`-prefixed big-endian
+    /// hex strings.
     #[wasm_bindgen(js_name = getInstanceValues)]
-    pub fn get_instance_values(&mut self, col: usize) -> JsValue {
-        let values: Vec<&Fr> = self
-            .public
-            .get(col)
-            .unwrap()
+    pub fn get_instance_values(&mut self, col: usize) -> Result<JsValue, Halo2WasmError> {
+        let values: Vec<String> = self
+            .instance_column(col)?
             .iter()
-            .map(|x| x.value())
+            .map(|x| fr_to_hex(x.value()))
             .collect();
-        let values: Vec<String> = values.iter().map(|x| format!("{x:?}")).collect();
-        serde_wasm_bindgen::to_value(&values).unwrap()
+        serde_wasm_bindgen::to_value(&values).map_err(|e| Halo2WasmError::Serialize(e.to_string()))
+    }
+
+    /// Returns the values of the instances in virtual column `col` with the given encoding.
+    #[wasm_bindgen(js_name = getInstanceValuesEncoded)]
+    pub fn get_instance_values_encoded(
+        &mut self,
+        col: usize,
+        encoding: FieldEncoding,
+    ) -> Result<js_sys::Array, Halo2WasmError> {
+        Ok(self
+            .instance_column(col)?
+            .iter()
+            .map(|x| JsValue::from(encode_fr(x.value(), encoding)))
+            .collect())
     }
 
-    pub fn config(&mut self, config: CircuitConfig) {
+    pub fn config(&mut self, config: CircuitConfig) -> Result<(), Halo2WasmError> {
+        let instance_columns = config
+            .instance_columns
+            .unwrap_or_else(|| vec![0; config.num_virtual_instance]);
+        check_instance_columns(
+            &instance_columns,
+            config.num_virtual_instance,
+            config.num_instance,
+        )?;
         let params = BaseCircuitParams {
             k: config.k,
+            // `BaseCircuitBuilder` only synthesizes the first phase, so later phases get none.
             num_advice_per_phase: vec![config.num_advice, 0, 0],
             num_lookup_advice_per_phase: vec![config.num_lookup_advice, 0, 0],
-            num_fixed: 1,
+            num_fixed: config.num_fixed.unwrap_or(1),
             lookup_bits: Some(config.num_lookup_bits),
             num_instance_columns: config.num_instance,
         };
-        self.circuit_params = Some(params);
+        self.circuit_params = Some(params.clone());
         self.public = std::iter::repeat_with(std::vec::Vec::new)
             .take(config.num_virtual_instance)
             .collect();
-        self.clear();
+        self.instance_columns = instance_columns;
+        self.break_points = None;
+        self.reset_circuit(params, false);
+        Ok(())
+    }
+
+    /// Chooses a config for the circuit that has already been run: the smallest `k` in
+    /// `[min_k, max_k]` (and greater than `lookup_bits`) whose advice columns fit in `max_advice`
+    /// (unbounded if unset), with the minimal column counts for that `k`.
+    ///
+    /// The circuit is re-configured with, and cleared for, the returned config, so it must be run
+    /// again. Cells are counted with the lookup bits the circuit was run with, so `lookup_bits`
+    /// should not be smaller than those.
+    #[wasm_bindgen(js_name = autoConfig)]
+    pub fn auto_config(
+        &mut self,
+        min_k: usize,
+        max_k: usize,
+        lookup_bits: usize,
+        max_advice: Option<usize>,
+    ) -> Result<CircuitConfig, Halo2WasmError> {
+        let num_instance = self.circuit_params()?.num_instance_columns;
+        let params = self.fit_params(min_k.max(lookup_bits + 1), max_k, max_advice)?;
+        let config = CircuitConfig {
+            k: params.k,
+            num_advice: params.num_advice_per_phase[0].max(1),
+            num_lookup_advice: params.num_lookup_advice_per_phase[0],
+            num_instance,
+            num_lookup_bits: lookup_bits,
+            num_virtual_instance: self.public.len(),
+            num_fixed: Some(params.num_fixed.max(1)),
+            instance_columns: Some(self.instance_columns.clone()),
+        };
+        self.config(config.clone())?;
+        Ok(config)
+    }
+
+    /// Returns the thread break points computed during `genVk`/`genPk` (or set with
+    /// `setBreakPoints`), as one array of row offsets per phase.
+    #[wasm_bindgen(js_name = getBreakPoints)]
+    pub fn get_break_points(&self) -> Result<JsValue, Halo2WasmError> {
+        let break_points = self
+            .break_points
+            .as_ref()
+            .ok_or(Halo2WasmError::BreakPointsMissing)?;
+        serde_wasm_bindgen::to_value(break_points)
+            .map_err(|e| Halo2WasmError::Serialize(e.to_string()))
+    }
+
+    /// Sets the thread break points from keygen, as returned by `getBreakPoints`.
+    ///
+    /// This clears the circuit and switches it to witness generation only mode, so `prove` lays out
+    /// the witness exactly as at keygen. `genVk`, `genPk` and `mock` are unavailable in this mode
+    /// until `config` is called again.
+    #[wasm_bindgen(js_name = setBreakPoints)]
+    pub fn set_break_points(&mut self, break_points: JsValue) -> Result<(), Halo2WasmError> {
+        let break_points: MultiPhaseThreadBreakPoints =
+            serde_wasm_bindgen::from_value(break_points)
+                .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        self.use_break_points(break_points)?;
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = getCircuitStats)]
-    pub fn get_circuit_stats(&mut self) -> CircuitStats {
+    pub fn get_circuit_stats(&mut self) -> Result<CircuitStats, Halo2WasmError> {
+        let k = self.circuit_params()?.k;
         let statistics = self.circuit.borrow_mut().statistics();
         let advice = statistics.gate.total_advice_per_phase[0];
         let lookup = statistics.total_lookup_advice_per_phase[0];
         let fixed = statistics.gate.total_fixed;
-        let k = self.circuit_params.clone().unwrap().k;
-        let instance = concat(self.public.clone()).len();
+        let instance = self.public.iter().map(Vec::len).sum();
 
-        CircuitStats {
+        Ok(CircuitStats {
             advice,
             lookup,
             fixed,
             instance,
             k,
-        }
+        })
     }
 
     #[wasm_bindgen(js_name = getVk)]
-    pub fn get_vk(&self) -> Vec<u8> {
+    pub fn get_vk(&self) -> Result<Vec<u8>, Halo2WasmError> {
         let file = self
-            .vk
-            .as_ref()
-            .unwrap()
+            .vk()?
             .to_bytes(halo2_base::halo2_proofs::SerdeFormat::RawBytesUnchecked);
-        file
+        Ok(file)
     }
 
     #[wasm_bindgen(js_name = getPartialVk)]
-    pub fn get_partial_vk(&self) -> Vec<u8> {
-        let vk = self.vk.as_ref().unwrap();
+    pub fn get_partial_vk(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        let vk = self.vk()?;
         let preprocessed = vk
             .fixed_commitments()
             .iter()
@@ -255,91 +401,200 @@ impl Halo2Wasm {
             transcript_initial_state,
         };
 
-        write_partial_vkey(&partial_vk).expect("Write partial vk should not fail")
+        let file = write_partial_vkey(&partial_vk)
+            .map_err(|e| Halo2WasmError::Serialize(e.to_string()))?;
+        Ok(file)
     }
 
     #[wasm_bindgen(js_name = getPk)]
-    pub fn get_pk(&self) -> Vec<u8> {
+    pub fn get_pk(&self) -> Result<Vec<u8>, Halo2WasmError> {
         let file = self
-            .pk
-            .as_ref()
-            .unwrap()
+            .pk()?
             .to_bytes(halo2_base::halo2_proofs::SerdeFormat::RawBytesUnchecked);
-        file
+        Ok(file)
+    }
+
+    /// Returns the bincode serialized `BaseCircuitParams`, as expected by `Halo2Verifier`.
+    #[wasm_bindgen(js_name = getCircuitParams)]
+    pub fn get_circuit_params(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        let file = bincode::serialize(self.circuit_params()?)
+            .map_err(|e| Halo2WasmError::Serialize(e.to_string()))?;
+        Ok(file)
+    }
+
+    /// Returns the minimal params needed by `Halo2Verifier`, which are much smaller than the full params.
+    #[wasm_bindgen(js_name = getVerifierParams)]
+    pub fn get_verifier_params(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        let mut file = Vec::new();
+        verifier_params(self.params()?)
+            .write(&mut file)
+            .map_err(|e| Halo2WasmError::Serialize(e.to_string()))?;
+        Ok(file)
     }
 
     #[wasm_bindgen(js_name = assignInstances)]
     pub fn assign_instances(&mut self) {
-        let flattened: Vec<AssignedValue<Fr>> = concat(self.public.clone());
-        self.circuit.borrow_mut().assigned_instances = vec![flattened];
-    }
-
-    pub fn mock(&mut self) {
-        let circuit = &*self.circuit.borrow_mut();
-        let flattened: Vec<AssignedValue<Fr>> = concat(self.public.clone());
-        let instances = flattened.iter().map(|x| *x.value()).collect();
-        MockProver::run(
-            self.circuit_params.as_ref().unwrap().k as u32,
-            circuit,
-            vec![instances],
-        )
-        .unwrap()
-        .assert_satisfied();
+        self.circuit.borrow_mut().assigned_instances = self.instance_cells();
+    }
+
+    pub fn mock(&mut self) -> Result<(), Halo2WasmError> {
+        let failures = self.mock_failures()?;
+        if !failures.is_empty() {
+            return Err(Halo2WasmError::MockFailed(failures.iter().join("\n")));
+        }
+        Ok(())
+    }
+
+    /// Runs the mock prover and returns every failure found instead of throwing on the first one.
+    #[wasm_bindgen(js_name = mockCheck)]
+    pub fn mock_check(&mut self) -> Result<MockCheckResult, Halo2WasmError> {
+        let failures = self.mock_failures()?;
+        let failures = if failures.is_empty() {
+            vec![]
+        } else {
+            let locator = CellLocator::new(&self.circuit.borrow());
+            failures
+                .iter()
+                .map(|f| MockFailure::from_verify_failure(f, &locator))
+                .collect_vec()
+        };
+        Ok(MockCheckResult {
+            satisfied: failures.is_empty(),
+            failures,
+        })
     }
 
     #[wasm_bindgen(js_name = loadParams)]
-    pub fn load_params(&mut self, params: &[u8]) {
-        let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params)).unwrap();
+    pub fn load_params(&mut self, params: &[u8]) -> Result<(), Halo2WasmError> {
+        let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params))
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
         self.params = Some(params);
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = loadVk)]
-    pub fn load_vk(&mut self, vk: &[u8]) {
-        let vk_reader = &mut BufReader::new(vk);
-        let params = self.circuit_params.clone().unwrap();
-        let vk = VerifyingKey::<G1Affine>::read::<BufReader<&[u8]>, BaseCircuitBuilder<Fr>>(
-            vk_reader,
-            halo2_base::halo2_proofs::SerdeFormat::RawBytesUnchecked,
-            params,
-        )
-        .unwrap();
-        self.vk = Some(vk);
+    pub fn load_vk(&mut self, vk: &[u8]) -> Result<(), Halo2WasmError> {
+        let params = self.circuit_params()?.clone();
+        self.vk = Some(read_vk(vk, params)?);
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = loadPk)]
-    pub fn load_pk(&mut self, pk: &[u8]) {
-        let pk_reader = &mut BufReader::new(pk);
-        let params = self.circuit_params.clone().unwrap();
-        let pk = ProvingKey::<G1Affine>::read::<BufReader<&[u8]>, BaseCircuitBuilder<Fr>>(
-            pk_reader,
-            halo2_base::halo2_proofs::SerdeFormat::RawBytesUnchecked,
-            params,
-        )
-        .unwrap();
-        self.pk = Some(pk);
+    pub fn load_pk(&mut self, pk: &[u8]) -> Result<(), Halo2WasmError> {
+        let params = self.circuit_params()?.clone();
+        self.pk = Some(read_pk(pk, params)?);
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = genVk)]
-    pub fn gen_vk(&mut self) {
-        let params = self.params.as_ref().unwrap();
-        let vk = keygen_vk(params, &*self.circuit.borrow()).expect("vk should not fail");
+    pub fn gen_vk(&mut self) -> Result<(), Halo2WasmError> {
+        self.check_keygen_mode()?;
+        let params = self.params()?;
+        let vk = keygen_vk(params, &*self.circuit.borrow())
+            .map_err(|e| Halo2WasmError::Synthesis(e.to_string()))?;
         self.vk = Some(vk);
+        self.break_points = Some(self.circuit.borrow().break_points());
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = genPk)]
-    pub fn gen_pk(&mut self) {
-        let vk = self.vk.clone().unwrap();
-        let params = self.params.as_ref().unwrap();
-        let pk = keygen_pk(params, vk, &*self.circuit.borrow()).expect("pk should not fail");
+    pub fn gen_pk(&mut self) -> Result<(), Halo2WasmError> {
+        self.check_keygen_mode()?;
+        let vk = self.vk()?.clone();
+        let params = self.params()?;
+        let pk = keygen_pk(params, vk, &*self.circuit.borrow())
+            .map_err(|e| Halo2WasmError::Synthesis(e.to_string()))?;
         self.pk = Some(pk);
+        self.break_points = Some(self.circuit.borrow().break_points());
+        Ok(())
     }
 
-    pub fn prove(&self) -> Vec<u8> {
-        let params = self.params.as_ref().unwrap();
-        let pk = self.pk.as_ref().unwrap();
-        let circuit = self.circuit.borrow().deep_clone();
-        let snark = gen_snark_shplonk(params, pk, circuit, None::<&str>);
-        snark.proof
+    /// Serializes the circuit configuration, break points, number of virtual instance columns
+    /// and verifying key (and optionally the proving key) into a single versioned artifact.
+    ///
+    /// Requires keys to have been generated with `genVk`/`genPk` or imported from an artifact.
+    #[wasm_bindgen(js_name = exportCircuitArtifact)]
+    pub fn export_circuit_artifact(&self, include_pk: bool) -> Result<Vec<u8>, Halo2WasmError> {
+        let format = halo2_base::halo2_proofs::SerdeFormat::RawBytesUnchecked;
+        let pk = if include_pk {
+            Some(self.pk()?.to_bytes(format))
+        } else {
+            None
+        };
+        let artifact = CircuitArtifact {
+            circuit_params: self.circuit_params()?.clone(),
+            break_points: self
+                .break_points
+                .clone()
+                .ok_or(Halo2WasmError::BreakPointsMissing)?,
+            instance_columns: self.instance_columns.clone(),
+            vk: self.vk()?.to_bytes(format),
+            pk,
+        };
+        artifact.to_bytes()
+    }
+
+    /// Configures the circuit and loads its keys from an artifact created by `exportCircuitArtifact`.
+    ///
+    /// Replaces any existing configuration and clears the circuit. If the artifact bundles a
+    /// proving key, the circuit is switched to witness generation only mode (see `setBreakPoints`).
+    #[wasm_bindgen(js_name = importCircuitArtifact)]
+    pub fn import_circuit_artifact(&mut self, artifact: &[u8]) -> Result<(), Halo2WasmError> {
+        let artifact = CircuitArtifact::from_bytes(artifact)?;
+        check_instance_columns(
+            &artifact.instance_columns,
+            artifact.instance_columns.len(),
+            artifact.circuit_params.num_instance_columns,
+        )?;
+        let vk = read_vk(&artifact.vk, artifact.circuit_params.clone())?;
+        let pk = artifact
+            .pk
+            .as_ref()
+            .map(|pk| read_pk(pk, artifact.circuit_params.clone()))
+            .transpose()?;
+
+        self.circuit_params = Some(artifact.circuit_params.clone());
+        self.public = std::iter::repeat_with(std::vec::Vec::new)
+            .take(artifact.instance_columns.len())
+            .collect();
+        self.instance_columns = artifact.instance_columns;
+        self.break_points = Some(artifact.break_points);
+        self.reset_circuit(artifact.circuit_params, pk.is_some());
+        self.vk = Some(vk);
+        self.pk = pk;
+        Ok(())
+    }
+
+    /// Encodes `proof` with the current instances as EVM calldata: every instance as a 32-byte
+    /// big-endian word, column by column, followed by the proof bytes.
+    ///
+    /// The proof must have been created with the EVM transcript to be accepted by a Solidity
+    /// verifier.
+    #[wasm_bindgen(js_name = encodeCalldata)]
+    pub fn encode_calldata(&self, proof: &[u8]) -> Vec<u8> {
+        evm::encode_calldata(&self.instance_values(), proof)
+    }
+
+    /// Sets the transcript used by `prove`, `proveSnark`, `verify` and `verifyProof`,
+    /// defaults to Poseidon.
+    ///
+    /// Use the EVM transcript for proofs verified by a Solidity verifier. Only Poseidon proofs
+    /// can be aggregated.
+    #[wasm_bindgen(js_name = setTranscript)]
+    pub fn set_transcript(&mut self, transcript: TranscriptType) {
+        self.transcript = transcript;
+    }
+
+    pub fn prove(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        Ok(self.gen_snark()?.proof)
+    }
+
+    /// Proves the circuit and returns the serialized snark (protocol, instances and proof)
+    /// together with the transcript used. Poseidon snarks can be read back with
+    /// `snark::load_snark` for aggregation.
+    #[wasm_bindgen(js_name = proveSnark)]
+    pub fn prove_snark(&self) -> Result<Vec<u8>, Halo2WasmError> {
+        snark_to_bytes(self.gen_snark()?, self.transcript)
     }
 
     /// For console logging only.
@@ -348,6 +603,191 @@ impl Halo2Wasm {
     }
 }
 
+#[cfg(feature = "prover")]
+impl Halo2Wasm {
+    /// Verifies `proof` against `instances`, one vector per instance column.
+    ///
+    /// Returns `Ok(false)` if the proof is invalid and `Err` if verification could not be run.
+    pub fn verify_proof_with_instances(
+        &self,
+        proof: &[u8],
+        instances: &[Vec<Fr>],
+    ) -> Result<bool, Halo2WasmError> {
+        match self.verify_instances(proof, instances) {
+            Ok(()) => Ok(true),
+            Err(Halo2WasmError::VerifyFailed(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn verify_instances(&self, proof: &[u8], instances: &[Vec<Fr>]) -> Result<(), Halo2WasmError> {
+        verify_shplonk(
+            self.params()?,
+            self.vk()?,
+            proof,
+            instances,
+            self.transcript,
+        )
+    }
+
+    fn gen_snark(&self) -> Result<Snark, Halo2WasmError> {
+        let params = self.params()?;
+        let pk = self.pk()?;
+        let circuit = self.circuit.borrow().deep_clone();
+        gen_snark(params, pk, circuit, self.transcript)
+    }
+
+    fn circuit_params(&self) -> Result<&BaseCircuitParams, Halo2WasmError> {
+        self.circuit_params
+            .as_ref()
+            .ok_or(Halo2WasmError::NotConfigured)
+    }
+
+    fn params(&self) -> Result<&ParamsKZG<Bn256>, Halo2WasmError> {
+        self.params.as_ref().ok_or(Halo2WasmError::ParamsMissing)
+    }
+
+    fn vk(&self) -> Result<&VerifyingKey<G1Affine>, Halo2WasmError> {
+        self.vk.as_ref().ok_or(Halo2WasmError::VkMissing)
+    }
+
+    fn pk(&self) -> Result<&ProvingKey<G1Affine>, Halo2WasmError> {
+        self.pk.as_ref().ok_or(Halo2WasmError::PkMissing)
+    }
+
+    fn instance_column(&self, col: usize) -> Result<&Vec<AssignedValue<Fr>>, Halo2WasmError> {
+        self.public
+            .get(col)
+            .ok_or(Halo2WasmError::InstanceColumnOutOfRange {
+                col,
+                num_columns: self.public.len(),
+            })
+    }
+
+    /// Returns the cells of every instance column, concatenating the virtual instances
+    /// assigned to it.
+    fn instance_cells(&self) -> Vec<Vec<AssignedValue<Fr>>> {
+        let num_instance = self
+            .circuit_params
+            .as_ref()
+            .map_or(0, |params| params.num_instance_columns);
+        let mut columns = vec![vec![]; num_instance];
+        for (public, &col) in self.public.iter().zip_eq(&self.instance_columns) {
+            columns[col].extend_from_slice(public);
+        }
+        columns
+    }
+
+    fn instance_values(&self) -> Vec<Vec<Fr>> {
+        self.instance_cells()
+            .iter()
+            .map(|col| col.iter().map(|x| *x.value()).collect())
+            .collect()
+    }
+
+    fn use_break_points(
+        &mut self,
+        break_points: MultiPhaseThreadBreakPoints,
+    ) -> Result<(), Halo2WasmError> {
+        let circuit_params = self.circuit_params()?.clone();
+        self.break_points = Some(break_points);
+        self.reset_circuit(circuit_params, true);
+        Ok(())
+    }
+
+    fn mock_failures(&self) -> Result<Vec<VerifyFailure>, Halo2WasmError> {
+        self.check_keygen_mode()?;
+        let k = self.circuit_params()?.k as u32;
+        let circuit = &*self.circuit.borrow();
+        let prover = MockProver::run(k, circuit, self.instance_values())
+            .map_err(|e| Halo2WasmError::Synthesis(e.to_string()))?;
+        Ok(prover.verify().err().unwrap_or_default())
+    }
+
+    /// Returns the params of the smallest `k` in `[min_k, max_k]` whose advice columns fit in
+    /// `max_advice`, leaving the circuit's own params unchanged.
+    fn fit_params(
+        &self,
+        min_k: usize,
+        max_k: usize,
+        max_advice: Option<usize>,
+    ) -> Result<BaseCircuitParams, Halo2WasmError> {
+        let mut circuit = self.circuit.borrow_mut();
+        // `set_k` and `calculate_params` overwrite the params the circuit is configured with.
+        let config_params = circuit.config_params.clone();
+        let params = (min_k..=max_k)
+            .map(|k| {
+                circuit.set_k(k);
+                circuit.calculate_params(Some(MINIMUM_ROWS))
+            })
+            .find(|params| max_advice.map_or(true, |max| params.num_advice_per_phase[0] <= max));
+        circuit.config_params = config_params;
+        params.ok_or_else(|| {
+            Halo2WasmError::AutoConfigFailed(format!(
+                "no k in [{min_k}, {max_k}] fits the circuit in {max_advice:?} advice columns"
+            ))
+        })
+    }
+
+    fn check_keygen_mode(&self) -> Result<(), Halo2WasmError> {
+        if self.circuit.borrow().witness_gen_only() {
+            return Err(Halo2WasmError::WitnessGenOnly);
+        }
+        Ok(())
+    }
+
+    /// Replaces the circuit with an empty one, invalidating the value handles of the old one. In
+    /// witness generation only mode the stored break points are used, otherwise they are
+    /// recomputed at keygen.
+    fn reset_circuit(&mut self, circuit_params: BaseCircuitParams, witness_gen_only: bool) {
+        let circuit = match &self.break_points {
+            Some(break_points) if witness_gen_only => {
+                BaseCircuitBuilder::prover(circuit_params, break_points.clone())
+            }
+            _ => BaseCircuitBuilder::new(false).use_params(circuit_params),
+        };
+        self.circuit.replace(circuit);
+        self.handles.borrow_mut().clear();
+        self.clear_instances();
+    }
+}
+
+/// Checks that each of `num_virtual_instance` virtual instances is mapped to an existing
+/// instance column.
+#[cfg(feature = "prover")]
+fn check_instance_columns(
+    instance_columns: &[usize],
+    num_virtual_instance: usize,
+    num_instance: usize,
+) -> Result<(), Halo2WasmError> {
+    if instance_columns.len() != num_virtual_instance {
+        return Err(Halo2WasmError::InvalidInstanceColumns(format!(
+            "expected an instance column for each of {num_virtual_instance} virtual instances, \
+             found {}",
+            instance_columns.len()
+        )));
+    }
+    if let Some(&col) = instance_columns.iter().find(|&&col| col >= num_instance) {
+        return Err(Halo2WasmError::InvalidInstanceColumns(format!(
+            "instance column {col} out of range, circuit has {num_instance} instance columns"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "prover")]
+fn read_pk(
+    pk: &[u8],
+    circuit_params: BaseCircuitParams,
+) -> Result<ProvingKey<G1Affine>, Halo2WasmError> {
+    ProvingKey::<G1Affine>::read::<BufReader<&[u8]>, BaseCircuitBuilder<Fr>>(
+        &mut BufReader::new(pk),
+        halo2_base::halo2_proofs::SerdeFormat::RawBytesUnchecked,
+        circuit_params,
+    )
+    .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))
+}
+
 #[wasm_bindgen(js_name = initPanicHook)]
 pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use halo2_base::{
+    gates::circuit::builder::BaseCircuitBuilder,
+    halo2_proofs::{
+        dev::{
+            metadata::{Column, VirtualCell},
+            FailureLocation, VerifyFailure,
+        },
+        halo2curves::bn256::Fr,
+        plonk::{Any, Circuit, ConstraintSystem},
+    },
+    AssignedValue,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::utils::fr_to_hex;
+
+/// Result of running the mock prover, see `Halo2Wasm::mock_check`.
+#[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct MockCheckResult {
+    pub satisfied: bool,
+    pub failures: Vec<MockFailure>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MockFailureKind {
+    Constraint,
+    ConstraintPoisoned,
+    Lookup,
+    Permutation,
+    CellNotAssigned,
+    Other,
+}
+
+/// A single unsatisfied constraint, lookup or permutation reported by the mock prover.
+#[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockFailure {
+    pub kind: MockFailureKind,
+    /// Human readable description of the failure.
+    pub message: String,
+    /// The failing constraint, including the gate it belongs to.
+    pub constraint: Option<String>,
+    /// The gate with an unassigned cell.
+    pub gate: Option<String>,
+    pub region: Option<String>,
+    /// Row offset within `region`.
+    pub offset: Option<usize>,
+    /// Absolute row, for failures outside of any region.
+    pub row: Option<usize>,
+    pub column: Option<String>,
+    /// Cells queried by the failing constraint and their values.
+    pub cell_values: Vec<MockCellValue>,
+    pub lookup_index: Option<usize>,
+    /// Cells of the threads at the failure's location: the cells queried by a failing
+    /// constraint, the looked up cell of a failing lookup, or the copied cell of a failing
+    /// permutation.
+    pub cells: Vec<MockCell>,
+}
+
+#[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockCellValue {
+    pub cell: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockCell {
+    pub thread: usize,
+    /// Offset of the cell in its thread.
+    pub offset: usize,
+    pub value: String,
+    /// The operation that assigned the cell, filled in by `Halo2LibWasm.annotateMockCheck`.
+    #[tsify(optional)]
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl MockFailure {
+    fn new(kind: MockFailureKind, failure: &VerifyFailure) -> Self {
+        Self {
+            kind,
+            message: failure.to_string(),
+            constraint: None,
+            gate: None,
+            region: None,
+            offset: None,
+            row: None,
+            column: None,
+            cell_values: vec![],
+            lookup_index: None,
+            cells: vec![],
+        }
+    }
+
+    fn with_location(mut self, location: &FailureLocation) -> Self {
+        match location {
+            FailureLocation::InRegion { region, offset } => {
+                self.region = Some(region.to_string());
+                self.offset = Some(*offset);
+            }
+            FailureLocation::OutsideRegion { row } => {
+                self.row = Some(*row);
+            }
+        }
+        self
+    }
+
+    /// Converts a mock prover failure, attaching the cells at its location found by `locator`.
+    pub fn from_verify_failure(failure: &VerifyFailure, locator: &CellLocator) -> Self {
+        match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                cell_values,
+            } => Self {
+                constraint: Some(constraint.to_string()),
+                cell_values: cell_values
+                    .iter()
+                    .map(|(cell, value)| MockCellValue {
+                        cell: cell.to_string(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+                cells: cell_values
+                    .iter()
+                    .filter_map(|(cell, _)| locator.queried_cell(cell, location_row(location)))
+                    .collect(),
+                ..Self::new(MockFailureKind::Constraint, failure)
+            }
+            .with_location(location),
+            VerifyFailure::ConstraintPoisoned { constraint } => Self {
+                constraint: Some(constraint.to_string()),
+                ..Self::new(MockFailureKind::ConstraintPoisoned, failure)
+            },
+            VerifyFailure::Lookup {
+                lookup_index,
+                location,
+                ..
+            } => Self {
+                lookup_index: Some(*lookup_index),
+                cells: locator
+                    .lookup_input(*lookup_index, location_row(location))
+                    .into_iter()
+                    .collect(),
+                ..Self::new(MockFailureKind::Lookup, failure)
+            }
+            .with_location(location),
+            VerifyFailure::Permutation { column, location } => Self {
+                column: Some(format!("{column:?}")),
+                cells: locator
+                    .cell(column, location_row(location))
+                    .into_iter()
+                    .collect(),
+                ..Self::new(MockFailureKind::Permutation, failure)
+            }
+            .with_location(location),
+            VerifyFailure::CellNotAssigned {
+                gate,
+                region,
+                gate_offset,
+                column,
+                offset,
+            } => Self {
+                gate: Some(gate.to_string()),
+                region: Some(region.to_string()),
+                offset: Some(*gate_offset),
+                row: usize::try_from(*offset).ok(),
+                column: Some(format!("{column:?}")),
+                ..Self::new(MockFailureKind::CellNotAssigned, failure)
+            },
+            #[allow(unreachable_patterns)]
+            _ => Self::new(MockFailureKind::Other, failure),
+        }
+    }
+}
+
+/// Row of `location`. `BaseCircuitBuilder` assigns all cells in a single region starting at row
+/// 0, so offsets within it are rows.
+fn location_row(location: &FailureLocation) -> usize {
+    match location {
+        FailureLocation::InRegion { offset, .. } => *offset,
+        FailureLocation::OutsideRegion { row } => *row,
+    }
+}
+
+/// Rotations queried by the basic gate `a + b * c = d`.
+const GATE_ROTATIONS: [i32; 4] = [0, 1, 2, 3];
+
+/// Maps the advice cells of a synthesized circuit back to the cells of the threads that assigned
+/// them, following how `BaseCircuitBuilder` lays out the first phase.
+pub struct CellLocator {
+    /// Cells by advice column index and row.
+    cells: HashMap<(usize, usize), AssignedValue<Fr>>,
+    num_advice: usize,
+    /// Advice column index looked up by each lookup.
+    lookup_columns: Vec<usize>,
+}
+
+impl CellLocator {
+    /// `circuit` must have been synthesized in keygen mode, e.g. by the mock prover, so its
+    /// break points are set.
+    pub fn new(circuit: &BaseCircuitBuilder<Fr>) -> Self {
+        let mut meta = ConstraintSystem::default();
+        let config =
+            BaseCircuitBuilder::configure_with_params(&mut meta, circuit.config_params.clone());
+        let gate_columns = config.gate().basic_gates[0]
+            .iter()
+            .map(|gate| gate.value.index())
+            .collect_vec();
+        let lookup_columns = (0..meta.num_advice_columns())
+            .filter(|column| !gate_columns.contains(column))
+            .collect_vec();
+        let mut cells = HashMap::new();
+
+        // Threads fill the gate columns in order. At a break point the last cell of a column is
+        // copied to the first row of the next one.
+        let break_points = &circuit.break_points()[0];
+        let (mut column, mut row) = (0, 0);
+        for ctx in &circuit.core().phase_manager[0].threads {
+            for offset in 0..ctx.advice.len() {
+                let cell = ctx.get(offset as isize);
+                cells.insert((gate_columns[column], row), cell);
+                if break_points.get(column) == Some(&row) {
+                    column += 1;
+                    row = 0;
+                    cells.insert((gate_columns[column], row), cell);
+                }
+                row += 1;
+            }
+        }
+
+        // Looked up cells fill the lookup columns left to right, then top to bottom. Without
+        // lookup columns, cells are looked up in place in the single gate column.
+        let lookup_columns = if lookup_columns.is_empty() {
+            gate_columns[..1].to_vec()
+        } else {
+            let cells_to_lookup = circuit.lookup_manager()[0].cells_to_lookup.lock().unwrap();
+            let looked_up = cells_to_lookup.values().flatten().flatten();
+            for (i, cell) in looked_up.enumerate() {
+                let column = lookup_columns[i % lookup_columns.len()];
+                cells.insert((column, i / lookup_columns.len()), *cell);
+            }
+            lookup_columns
+        };
+        Self {
+            cells,
+            num_advice: meta.num_advice_columns(),
+            lookup_columns,
+        }
+    }
+
+    /// Returns the cell of the thread that assigned the advice cell at `column` and `row`.
+    pub fn locate(&self, column: usize, row: usize) -> Option<MockCell> {
+        let cell = self.cells.get(&(column, row))?;
+        let context_cell = cell.cell?;
+        Some(MockCell {
+            thread: context_cell.context_id,
+            offset: context_cell.offset,
+            value: fr_to_hex(cell.value()),
+            source: None,
+        })
+    }
+
+    /// Returns the thread cell at `row` of `column`, if it is an advice column.
+    pub fn cell(&self, column: &Column, row: usize) -> Option<MockCell> {
+        let column = (0..self.num_advice).find(|&i| Column::from((Any::advice(), i)) == *column)?;
+        self.locate(column, row)
+    }
+
+    /// Returns the thread cell queried as `cell` by a gate at `row`.
+    pub fn queried_cell(&self, cell: &VirtualCell, row: usize) -> Option<MockCell> {
+        let (column, rotation) = (0..self.num_advice)
+            .cartesian_product(GATE_ROTATIONS)
+            .find(|&(i, rotation)| {
+                VirtualCell::from((Column::from((Any::advice(), i)), rotation)) == *cell
+            })?;
+        self.locate(column, row + rotation as usize)
+    }
+
+    /// Returns the cell looked up by lookup `lookup_index` at `row`.
+    pub fn lookup_input(&self, lookup_index: usize, row: usize) -> Option<MockCell> {
+        self.locate(*self.lookup_columns.get(lookup_index)?, row)
+    }
+}
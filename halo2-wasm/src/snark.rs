@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use snark_verifier_sdk::Snark;
+
+use crate::{
+    transcript::TranscriptType,
+    utils::{from_versioned_bytes, to_versioned_bytes},
+    Halo2WasmError,
+};
+
+/// Magic bytes prefixing every serialized [`SnarkArtifact`].
+pub const SNARK_MAGIC: [u8; 4] = *b"H2WS";
+/// Current version of the serialized [`SnarkArtifact`] format.
+pub const SNARK_VERSION: u32 = 1;
+
+/// A snark (protocol, instances and proof) together with the transcript its proof was created
+/// with.
+///
+/// Serialized as `SNARK_MAGIC || SNARK_VERSION (u32 LE) || bincode(SnarkArtifact)`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnarkArtifact {
+    pub transcript: TranscriptType,
+    pub snark: Snark,
+}
+
+pub fn snark_to_bytes(snark: Snark, transcript: TranscriptType) -> Result<Vec<u8>, Halo2WasmError> {
+    let artifact = SnarkArtifact { transcript, snark };
+    to_versioned_bytes(SNARK_MAGIC, SNARK_VERSION, &artifact)
+}
+
+/// Reads a snark serialized by [`snark_to_bytes`], e.g. by `Halo2Wasm.proveSnark`.
+pub fn load_snark_artifact(bytes: &[u8]) -> Result<SnarkArtifact, Halo2WasmError> {
+    from_versioned_bytes(SNARK_MAGIC, SNARK_VERSION, "snark", bytes)
+}
+
+/// Reads a snark to aggregate, which must have been proven with a Poseidon transcript.
+pub fn load_snark(bytes: &[u8]) -> Result<Snark, Halo2WasmError> {
+    let artifact = load_snark_artifact(bytes)?;
+    if artifact.transcript != TranscriptType::Poseidon {
+        return Err(Halo2WasmError::TranscriptMismatch {
+            expected: TranscriptType::Poseidon,
+            found: artifact.transcript,
+        });
+    }
+    Ok(artifact.snark)
+}
@@ -0,0 +1,90 @@
+use crate::aggregation::{AggregationConfig, AggregationWasm};
+use crate::tests::utils::{get_testing_params, prove_sum};
+use crate::Halo2WasmError;
+
+/// Keeps keygen for a single small snark fast enough for the default test run.
+const SMALL_K: usize = 16;
+
+fn setup(k: usize, snarks: &[Vec<u8>], params: &[u8]) -> AggregationWasm {
+    let mut aggregation = AggregationWasm::new();
+    aggregation.config(AggregationConfig {
+        k,
+        lookup_bits: k - 1,
+        expose_previous_instances: true,
+    });
+    for snark in snarks {
+        aggregation.add_snark(snark).unwrap();
+    }
+    aggregation.load_params(params).unwrap();
+    aggregation
+}
+
+#[test]
+pub fn test_aggregate_snark_small_k() {
+    let (halo2wasm, _) = prove_sum("10", "15");
+    let snarks = [halo2wasm.prove_snark().unwrap()];
+    let params = get_testing_params(SMALL_K);
+
+    let mut aggregation = setup(SMALL_K, &snarks, &params);
+    assert_eq!(
+        aggregation.config_params(),
+        Err(Halo2WasmError::ConfigParamsMissing)
+    );
+    aggregation.gen_vk().unwrap();
+    aggregation.gen_pk().unwrap();
+    let proof = aggregation.prove().unwrap();
+    aggregation.verify(&proof).unwrap();
+    // 12 accumulator limbs followed by the instance of the snark.
+    assert_eq!(aggregation.instances()[0].len(), 12 + 1);
+
+    // Keys are kept when the snarks are replaced by ones of the same circuit.
+    aggregation.clear_snarks();
+    assert!(aggregation.instances().is_empty());
+    let (halo2wasm, _) = prove_sum("1", "2");
+    aggregation
+        .add_snark(&halo2wasm.prove_snark().unwrap())
+        .unwrap();
+    let proof = aggregation.prove().unwrap();
+    aggregation.verify(&proof).unwrap();
+
+    // Prove again with the keys loaded into a fresh instance.
+    let mut loaded = setup(SMALL_K, &snarks, &params);
+    loaded.set_config_params(aggregation.config_params().unwrap());
+    loaded.set_break_points(aggregation.break_points().unwrap().clone());
+    loaded.load_pk(&aggregation.get_pk().unwrap()).unwrap();
+    let proof = loaded.prove().unwrap();
+    loaded.verify(&proof).unwrap();
+
+    let vk = aggregation.get_vk().unwrap();
+    let mut verifier = setup(SMALL_K, &snarks, &params);
+    verifier.set_config_params(aggregation.config_params().unwrap());
+    verifier.load_vk(&vk).unwrap();
+    assert_eq!(verifier.get_vk().unwrap(), vk);
+}
+
+#[test]
+#[ignore = "aggregation keygen needs k = 20 params and takes minutes"]
+pub fn test_aggregate_snarks() {
+    let snarks = [prove_sum("10", "15"), prove_sum("1", "2")]
+        .map(|(halo2wasm, _)| halo2wasm.prove_snark().unwrap());
+
+    let mut aggregation = AggregationWasm::new();
+    aggregation.config(AggregationConfig {
+        k: 20,
+        lookup_bits: 19,
+        expose_previous_instances: true,
+    });
+    for snark in &snarks {
+        aggregation.add_snark(snark).unwrap();
+    }
+    aggregation.load_params(&get_testing_params(20)).unwrap();
+    aggregation.gen_vk().unwrap();
+    aggregation.gen_pk().unwrap();
+    let proof = aggregation.prove().unwrap();
+    aggregation.verify(&proof).unwrap();
+
+    // 12 accumulator limbs followed by the instances of each snark.
+    let instances = aggregation.instances();
+    assert_eq!(instances.len(), 1);
+    assert_eq!(instances[0].len(), 12 + 2);
+}
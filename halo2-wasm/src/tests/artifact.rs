@@ -0,0 +1,90 @@
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::artifact::{CircuitArtifact, ARTIFACT_MAGIC};
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::{get_testing_config, load_testing_params, prove_sum};
+use crate::verifier::Halo2Verifier;
+use crate::{Halo2Wasm, Halo2WasmError};
+
+#[test]
+pub fn test_circuit_artifact_roundtrip() {
+    let (halo2wasm, proof) = prove_sum("10", "15");
+    let bytes = halo2wasm.export_circuit_artifact(true).unwrap();
+    assert_eq!(bytes[..4], ARTIFACT_MAGIC);
+
+    let artifact = CircuitArtifact::from_bytes(&bytes).unwrap();
+    assert_eq!(artifact.circuit_params.k, get_testing_config().k);
+    assert_eq!(artifact.instance_columns, vec![0]);
+    assert_eq!(artifact.vk, halo2wasm.get_vk().unwrap());
+    assert_eq!(artifact.pk, Some(halo2wasm.get_pk().unwrap()));
+
+    let mut imported = Halo2Wasm::default();
+    imported.import_circuit_artifact(&bytes).unwrap();
+    load_testing_params(&mut imported, artifact.circuit_params.k);
+    assert_eq!(imported.get_vk().unwrap(), artifact.vk);
+    assert!(imported
+        .verify_proof_with_instances(&proof, &[vec![Fr::from(25)]])
+        .unwrap());
+
+    let verifier =
+        Halo2Verifier::from_artifact(&bytes, &halo2wasm.get_verifier_params().unwrap()).unwrap();
+    assert!(verifier
+        .verify_with_instances(&proof, &[vec![Fr::from(25)]])
+        .unwrap());
+}
+
+#[test]
+pub fn test_circuit_artifact_rejects_bad_header() {
+    let (halo2wasm, _) = prove_sum("10", "15");
+    let mut bytes = halo2wasm.export_circuit_artifact(false).unwrap();
+    bytes[4] += 1;
+    assert!(matches!(
+        CircuitArtifact::from_bytes(&bytes),
+        Err(Halo2WasmError::Deserialize(_))
+    ));
+    assert!(matches!(
+        CircuitArtifact::from_bytes(&bytes[4..]),
+        Err(Halo2WasmError::Deserialize(_))
+    ));
+}
+
+#[test]
+pub fn test_invalid_artifact_keeps_config() {
+    let (halo2wasm, _) = prove_sum("10", "15");
+    let mut artifact =
+        CircuitArtifact::from_bytes(&halo2wasm.export_circuit_artifact(false).unwrap()).unwrap();
+    artifact.instance_columns = vec![1];
+    let bytes = artifact.to_bytes().unwrap();
+
+    let mut imported = Halo2Wasm::default();
+    assert!(matches!(
+        imported.import_circuit_artifact(&bytes),
+        Err(Halo2WasmError::InvalidInstanceColumns(_))
+    ));
+    assert!(matches!(
+        imported.get_circuit_params(),
+        Err(Halo2WasmError::NotConfigured)
+    ));
+}
+
+#[test]
+pub fn test_prove_from_imported_artifact() {
+    let (halo2wasm, _) = prove_sum("10", "15");
+    let bytes = halo2wasm.export_circuit_artifact(true).unwrap();
+
+    let mut prover = Halo2Wasm::default();
+    prover.import_circuit_artifact(&bytes).unwrap();
+    assert!(prover.circuit.borrow().witness_gen_only());
+    let mut halo2libwasm = Halo2LibWasm::new(&prover).unwrap();
+    let a = halo2libwasm.witness("20").unwrap();
+    let b = halo2libwasm.witness("22").unwrap();
+    let c = halo2libwasm.add(a, b).unwrap();
+    halo2libwasm.make_public(&mut prover, c, 0).unwrap();
+    prover.assign_instances();
+    load_testing_params(&mut prover, get_testing_config().k);
+    let proof = prover.prove().unwrap();
+
+    assert!(halo2wasm
+        .verify_proof_with_instances(&proof, &[vec![Fr::from(42)]])
+        .unwrap());
+}
@@ -0,0 +1,117 @@
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::halo2lib::batch::{BatchOp, BATCH_OUTPUT_FLAG};
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::{base_test, get_testing_circuit_config};
+use crate::utils::fr_to_hex;
+use crate::{Halo2Wasm, Halo2WasmError};
+
+fn output(i: u32) -> u32 {
+    BATCH_OUTPUT_FLAG | i
+}
+
+/// `select((a + b) * c, a, a < c)` followed by `range_check(a, 8)`.
+fn batch_ops() -> (Vec<u32>, Vec<String>) {
+    let ops = vec![
+        BatchOp::Witness as u32,
+        0,
+        BatchOp::Witness as u32,
+        1,
+        BatchOp::Constant as u32,
+        2,
+        BatchOp::Add as u32,
+        output(0),
+        output(1),
+        BatchOp::Mul as u32,
+        output(3),
+        output(2),
+        BatchOp::IsLessThan as u32,
+        output(0),
+        output(2),
+        8,
+        BatchOp::Select as u32,
+        output(4),
+        output(0),
+        output(5),
+        BatchOp::RangeCheck as u32,
+        output(0),
+        8,
+    ];
+    let values = ["10", "15", "0x20"].map(String::from).to_vec();
+    (ops, values)
+}
+
+#[test]
+pub fn test_batch_matches_single_ops() {
+    let single = base_test().run_wasm_builder(|halo2lib| {
+        let a = halo2lib.witness("10").unwrap();
+        let b = halo2lib.witness("15").unwrap();
+        let c = halo2lib.constant("32").unwrap();
+        let sum = halo2lib.add(a, b).unwrap();
+        let prod = halo2lib.mul(sum, c).unwrap();
+        let lt = halo2lib.is_less_than(a, c, "8").unwrap();
+        halo2lib.select(prod, a, lt).unwrap();
+        halo2lib.range_check(a, "8").unwrap();
+    });
+    let batch = base_test().run_wasm_builder(|halo2lib| {
+        let (ops, values) = batch_ops();
+        halo2lib.execute_ops(&ops, &values).unwrap();
+    });
+    assert_eq!(single, batch);
+}
+
+#[test]
+pub fn test_batch_outputs() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let (ops, values) = batch_ops();
+    let outputs = halo2libwasm.execute_ops(&ops, &values).unwrap();
+    let outputs: Vec<String> = outputs
+        .into_iter()
+        .map(|x| halo2libwasm.value(x as usize).unwrap())
+        .collect();
+    let expected = [10, 15, 32, 25, 800, 1, 800].map(|x| fr_to_hex(&Fr::from(x)));
+    assert_eq!(outputs, expected);
+
+    // cells assigned outside of the batch can be used as operands
+    let a = halo2libwasm.witness("7").unwrap() as u32;
+    let ops = [BatchOp::Neg as u32, a, BatchOp::Add as u32, a, output(0)];
+    let outputs = halo2libwasm.execute_ops(&ops, &[]).unwrap();
+    assert_eq!(
+        halo2libwasm.value(outputs[1] as usize).unwrap(),
+        fr_to_hex(&Fr::zero())
+    );
+}
+
+#[test]
+pub fn test_invalid_batch() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let a = halo2libwasm.witness("1").unwrap() as u32;
+    // with 9 lookup bits `is_less_than` takes at most (253 / 9 - 1) * 9 = 243 bits
+    let invalid: [&[u32]; 8] = [
+        &[1000],
+        &[BatchOp::Add as u32, a],
+        &[BatchOp::Add as u32, a, a + 1],
+        &[BatchOp::Neg as u32, output(0)],
+        &[BatchOp::Witness as u32, 1],
+        &[BatchOp::RangeCheck as u32, a, 254],
+        &[BatchOp::CheckLessThan as u32, a, a, u32::MAX],
+        &[BatchOp::IsLessThan as u32, a, a, 244],
+    ];
+    for ops in invalid {
+        assert!(matches!(
+            halo2libwasm.execute_ops(ops, &["1".to_string()]),
+            Err(Halo2WasmError::InvalidBatch(_))
+        ));
+    }
+    // nothing is assigned by an invalid batch
+    let ops = [BatchOp::Witness as u32, 0, BatchOp::Neg as u32, output(1)];
+    assert!(halo2libwasm.execute_ops(&ops, &["2".to_string()]).is_err());
+    assert_eq!(halo2libwasm.witness("3").unwrap() as u32, a + 1);
+
+    let ops = [BatchOp::IsLessThan as u32, a, a, 243];
+    assert!(halo2libwasm.execute_ops(&ops, &[]).is_ok());
+}
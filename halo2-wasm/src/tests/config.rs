@@ -0,0 +1,101 @@
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::get_testing_circuit_config;
+use crate::{Halo2Wasm, Halo2WasmError};
+
+/// Loads `n` witnesses and range checks each of them to 64 bits.
+fn range_check_circuit(halo2libwasm: &mut Halo2LibWasm, n: usize) {
+    for i in 0..n {
+        let a = halo2libwasm.witness(&i.to_string()).unwrap();
+        halo2libwasm.range_check(a, "64").unwrap();
+    }
+}
+
+#[test]
+pub fn test_auto_config_min_k() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    range_check_circuit(&mut halo2libwasm, 100);
+
+    let config = halo2wasm.auto_config(6, 16, 8, None).unwrap();
+    assert_eq!(config.k, 9);
+    assert_eq!(config.num_lookup_bits, 8);
+
+    halo2libwasm.config().unwrap();
+    range_check_circuit(&mut halo2libwasm, 100);
+    halo2wasm.assign_instances();
+    halo2wasm.mock().unwrap();
+}
+
+#[test]
+pub fn test_auto_config_max_advice() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    range_check_circuit(&mut halo2libwasm, 1000);
+
+    let unbounded = halo2wasm.auto_config(9, 16, 8, None).unwrap();
+    halo2libwasm.config().unwrap();
+    range_check_circuit(&mut halo2libwasm, 1000);
+    let bounded = halo2wasm.auto_config(9, 16, 8, Some(2)).unwrap();
+    assert!(bounded.num_advice <= 2);
+    assert!(bounded.k > unbounded.k);
+
+    halo2libwasm.config().unwrap();
+    range_check_circuit(&mut halo2libwasm, 1000);
+    halo2wasm.assign_instances();
+    halo2wasm.mock().unwrap();
+}
+
+#[test]
+pub fn test_auto_config_failure_keeps_config() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    range_check_circuit(&mut halo2libwasm, 100);
+
+    let params = halo2wasm.circuit.borrow().config_params.clone();
+    assert!(matches!(
+        halo2wasm.fit_params(9, 10, Some(1)),
+        Err(Halo2WasmError::AutoConfigFailed(_))
+    ));
+    let config_params = halo2wasm.circuit.borrow().config_params.clone();
+    assert_eq!(config_params.k, params.k);
+    assert_eq!(
+        config_params.num_advice_per_phase,
+        params.num_advice_per_phase
+    );
+
+    // the circuit is still usable with the config it was run with
+    halo2wasm.assign_instances();
+    halo2wasm.mock().unwrap();
+}
+
+#[test]
+pub fn test_not_configured() {
+    let mut halo2wasm = Halo2Wasm::default();
+    assert!(matches!(
+        Halo2LibWasm::new(&halo2wasm),
+        Err(Halo2WasmError::NotConfigured)
+    ));
+    assert!(matches!(
+        halo2wasm.clear(),
+        Err(Halo2WasmError::NotConfigured)
+    ));
+    assert!(matches!(
+        halo2wasm.get_circuit_params(),
+        Err(Halo2WasmError::NotConfigured)
+    ));
+}
+
+#[test]
+pub fn test_keys_missing() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    assert!(matches!(halo2wasm.get_vk(), Err(Halo2WasmError::VkMissing)));
+    assert!(matches!(halo2wasm.get_pk(), Err(Halo2WasmError::PkMissing)));
+    assert!(matches!(
+        halo2wasm.prove(),
+        Err(Halo2WasmError::ParamsMissing)
+    ));
+}
@@ -0,0 +1,157 @@
+use halo2_base::{halo2_proofs::halo2curves::bn256::Fr, utils::modulus};
+
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::get_testing_circuit_config;
+use crate::utils::{
+    decode_fr, encode_fr, fr_from_bytes_be, fr_from_str, fr_to_hex, parse_int, EncodedFr,
+    FieldEncoding,
+};
+use crate::{Halo2Wasm, Halo2WasmError};
+
+const ENCODINGS: [FieldEncoding; 4] = [
+    FieldEncoding::Decimal,
+    FieldEncoding::Hex,
+    FieldEncoding::BytesLe,
+    FieldEncoding::BytesBe,
+];
+
+#[test]
+pub fn test_encode_fr() {
+    let x = Fr::from(258);
+    assert_eq!(
+        encode_fr(&x, FieldEncoding::Decimal),
+        EncodedFr::String("258".to_string())
+    );
+    assert_eq!(
+        encode_fr(&x, FieldEncoding::Hex),
+        EncodedFr::String(format!("0x{}0102", "0".repeat(60)))
+    );
+    let mut le = vec![0u8; 32];
+    le[..2].copy_from_slice(&[2, 1]);
+    assert_eq!(encode_fr(&x, FieldEncoding::BytesLe), EncodedFr::Bytes(le));
+    let mut be = vec![0u8; 32];
+    be[30..].copy_from_slice(&[1, 2]);
+    assert_eq!(encode_fr(&x, FieldEncoding::BytesBe), EncodedFr::Bytes(be));
+    assert_eq!(fr_to_hex(&x), format!("{x:?}"));
+}
+
+#[test]
+pub fn test_encoding_roundtrip() {
+    for x in [Fr::zero(), Fr::from(25), -Fr::one()] {
+        for encoding in ENCODINGS {
+            let encoded = encode_fr(&x, encoding);
+            assert_eq!(decode_fr(&encoded, encoding).unwrap(), x);
+        }
+        let decimal = match encode_fr(&x, FieldEncoding::Decimal) {
+            EncodedFr::String(s) => s,
+            EncodedFr::Bytes(_) => unreachable!(),
+        };
+        assert_eq!(fr_from_str(&decimal).unwrap(), x);
+        assert_eq!(fr_from_str(&fr_to_hex(&x)).unwrap(), x);
+    }
+}
+
+#[test]
+pub fn test_decode_rejects_invalid() {
+    let p = modulus::<Fr>();
+    let invalid = [
+        (
+            EncodedFr::String(p.to_str_radix(10)),
+            FieldEncoding::Decimal,
+        ),
+        (
+            EncodedFr::String(format!("0x{}", p.to_str_radix(16))),
+            FieldEncoding::Hex,
+        ),
+        (EncodedFr::String("25".to_string()), FieldEncoding::Hex),
+        (
+            EncodedFr::String("0x19".to_string()),
+            FieldEncoding::Decimal,
+        ),
+        (EncodedFr::Bytes(vec![0xff; 32]), FieldEncoding::BytesLe),
+        (EncodedFr::Bytes(vec![0; 31]), FieldEncoding::BytesBe),
+        (EncodedFr::String("25".to_string()), FieldEncoding::BytesLe),
+    ];
+    for (value, encoding) in invalid {
+        assert!(matches!(
+            decode_fr(&value, encoding),
+            Err(Halo2WasmError::InvalidFieldElement(_))
+        ));
+    }
+}
+
+#[test]
+pub fn test_parse_int() {
+    assert_eq!(parse_int::<usize>(" 64 "), Ok(64));
+    for s in ["", "-1", "0x40", "64 bits"] {
+        assert_eq!(
+            parse_int::<usize>(s),
+            Err(Halo2WasmError::InvalidInteger(s.to_string()))
+        );
+    }
+}
+
+#[test]
+pub fn test_value_roundtrips_through_witness() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let a = halo2libwasm.witness("25").unwrap();
+    let value = halo2libwasm.value(a).unwrap();
+    assert_eq!(fr_from_str(&value).unwrap(), Fr::from(25));
+    let b = halo2libwasm.witness(&value).unwrap();
+    assert_eq!(halo2libwasm.value(b).unwrap(), value);
+}
+
+#[test]
+pub fn test_hex_and_byte_loaders() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let expected = fr_to_hex(&Fr::from(258));
+    let cells = [
+        halo2libwasm.witness("0x0102").unwrap(),
+        halo2libwasm.constant("0x102").unwrap(),
+        halo2libwasm.witness_bytes(&[1, 2]).unwrap(),
+        halo2libwasm.constant_bytes(&[0, 0, 1, 2]).unwrap(),
+    ];
+    for cell in cells {
+        assert_eq!(halo2libwasm.value(cell).unwrap(), expected);
+    }
+    let witnesses = halo2libwasm
+        .witnesses(vec!["1".to_string(), "0x2".to_string(), "3".to_string()])
+        .unwrap();
+    assert_eq!(witnesses.len(), 3);
+    for (cell, value) in witnesses.into_iter().zip(1u64..) {
+        assert_eq!(
+            halo2libwasm.value(cell as usize).unwrap(),
+            fr_to_hex(&Fr::from(value))
+        );
+    }
+}
+
+#[test]
+pub fn test_loaders_check_modulus() {
+    let p = modulus::<Fr>();
+    let p_minus_one = &p - 1u32;
+    assert_eq!(
+        fr_from_bytes_be(&p_minus_one.to_bytes_be()).unwrap(),
+        -Fr::one()
+    );
+    for invalid in [p.to_bytes_be(), vec![0; 33]] {
+        assert!(matches!(
+            fr_from_bytes_be(&invalid),
+            Err(Halo2WasmError::InvalidFieldElement(_))
+        ));
+    }
+    for invalid in [
+        p.to_str_radix(10),
+        format!("0x{}", p.to_str_radix(16)),
+        "0xzz".to_string(),
+    ] {
+        assert!(matches!(
+            fr_from_str(&invalid),
+            Err(Halo2WasmError::InvalidFieldElement(_))
+        ));
+    }
+}
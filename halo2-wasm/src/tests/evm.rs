@@ -0,0 +1,38 @@
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use snark_verifier::loader::evm::{compile_solidity, deploy_and_call};
+
+use crate::evm::encode_calldata;
+use crate::tests::utils::prove_sum;
+use crate::transcript::TranscriptType;
+
+#[test]
+#[ignore = "compiling the verifier needs solc on PATH"]
+pub fn test_gen_solidity_verifier() {
+    let (mut halo2wasm, _) = prove_sum("10", "15");
+    let deployment_code = compile_solidity(&halo2wasm.gen_solidity_verifier().unwrap());
+
+    halo2wasm.set_transcript(TranscriptType::Evm);
+    let proof = halo2wasm.prove().unwrap();
+    deploy_and_call(deployment_code.clone(), halo2wasm.encode_calldata(&proof)).unwrap();
+
+    // The same proof with another sum is rejected.
+    let calldata = encode_calldata(&[vec![Fr::from(26)]], &proof);
+    assert!(deploy_and_call(deployment_code, calldata).is_err());
+}
+
+#[test]
+pub fn test_encode_calldata() {
+    let (mut halo2wasm, _) = prove_sum("10", "15");
+    halo2wasm.set_transcript(TranscriptType::Evm);
+    let proof = halo2wasm.prove().unwrap();
+    let calldata = halo2wasm.encode_calldata(&proof);
+    assert_eq!(calldata, encode_calldata(&[vec![Fr::from(25)]], &proof));
+    assert_eq!(
+        calldata,
+        snark_verifier::loader::evm::encode_calldata(&[vec![Fr::from(25)]], &proof)
+    );
+    assert_eq!(calldata.len(), 32 + proof.len());
+    assert!(calldata[..31].iter().all(|&b| b == 0));
+    assert_eq!(calldata[31], 25);
+    assert_eq!(calldata[32..], proof);
+}
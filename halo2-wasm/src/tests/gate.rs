@@ -21,8 +21,8 @@ macro_rules! gate_test {
                     chip.$op(ctx, a);
                 });
                 let wasm = base_test().run_wasm_builder(|ctx| {
-                    let [a] = [$a].map(|x| ctx.constant(x));
-                    ctx.$op(a)
+                    let [a] = [$a].map(|x| ctx.constant(x).unwrap());
+                    ctx.$op(a).unwrap()
                 });
                 assert_eq!(base, wasm);
             }
@@ -37,8 +37,8 @@ macro_rules! gate_test {
                     chip.$op(ctx, a, b);
                 });
                 let wasm = base_test().run_wasm_builder(|ctx| {
-                    let [a, b] = [$a, $b].map(|x| ctx.constant(x));
-                    ctx.$op(a, b)
+                    let [a, b] = [$a, $b].map(|x| ctx.constant(x).unwrap());
+                    ctx.$op(a, b).unwrap()
                 });
                 assert_eq!(base, wasm);
             }
@@ -53,8 +53,8 @@ macro_rules! gate_test {
                     chip.$op(ctx, a, b, c);
                 });
                 let wasm = base_test().run_wasm_builder(|ctx| {
-                    let [a, b, c] = [$a, $b, $c].map(|x| ctx.constant(x));
-                    ctx.$op(a, b, c)
+                    let [a, b, c] = [$a, $b, $c].map(|x| ctx.constant(x).unwrap());
+                    ctx.$op(a, b, c).unwrap()
                 });
                 assert_eq!(base, wasm);
             }
@@ -102,8 +102,8 @@ gate_test!(
         chip.assert_is_const(ctx, &a, &fe);
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let a = ctx.constant(inputs[0]);
-        ctx.assert_is_const(a, inputs[0]);
+        let a = ctx.constant(inputs[0]).unwrap();
+        ctx.assert_is_const(a, inputs[0]).unwrap();
     }
 );
 
@@ -124,9 +124,10 @@ gate_test!(
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
         let inputs = inputs
             .iter()
-            .map(|x| ctx.constant(x).to_u32().unwrap())
+            .map(|x| ctx.constant(x).unwrap().to_u32().unwrap())
             .collect::<Vec<_>>();
-        ctx.inner_product(inputs.clone().as_slice(), inputs.as_slice());
+        ctx.inner_product(inputs.clone().as_slice(), inputs.as_slice())
+            .unwrap();
     }
 );
 
@@ -139,9 +140,9 @@ gate_test!(
         chip.pow_var(ctx, a, exp, inputs.2);
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str, &str, usize)| {
-        let a = ctx.constant(inputs.0);
-        let exp = ctx.constant(inputs.1);
-        ctx.pow_var(a, exp, &inputs.2.to_string());
+        let a = ctx.constant(inputs.0).unwrap();
+        let exp = ctx.constant(inputs.1).unwrap();
+        ctx.pow_var(a, exp, &inputs.2.to_string()).unwrap();
     }
 );
 
@@ -158,9 +159,9 @@ gate_test!(
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
         let inputs = inputs
             .iter()
-            .map(|x| ctx.constant(x).to_u32().unwrap())
+            .map(|x| ctx.constant(x).unwrap().to_u32().unwrap())
             .collect::<Vec<_>>();
-        ctx.sum(inputs.as_slice());
+        ctx.sum(inputs.as_slice()).unwrap();
     }
 );
 
@@ -177,9 +178,9 @@ gate_test!(
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
         let inputs = inputs
             .iter()
-            .map(|x| ctx.constant(x).to_u32().unwrap())
+            .map(|x| ctx.constant(x).unwrap().to_u32().unwrap())
             .collect::<Vec<_>>();
-        ctx.bits_to_indicator(inputs.as_slice());
+        ctx.bits_to_indicator(inputs.as_slice()).unwrap();
     }
 );
 
@@ -191,8 +192,8 @@ gate_test!(
         chip.idx_to_indicator(ctx, idx, inputs[1].parse().unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let idx = ctx.constant(inputs[0]);
-        ctx.idx_to_indicator(idx, inputs[1]);
+        let idx = ctx.constant(inputs[0]).unwrap();
+        ctx.idx_to_indicator(idx, inputs[1]).unwrap();
     }
 );
 
@@ -213,13 +214,14 @@ gate_test!(
     |ctx: &mut Halo2LibWasm, inputs: &[&[&str]]| {
         let a = inputs[0]
             .iter()
-            .map(|x| ctx.constant(x).to_u32().unwrap())
+            .map(|x| ctx.constant(x).unwrap().to_u32().unwrap())
             .collect::<Vec<_>>();
         let indicator = inputs[1]
             .iter()
-            .map(|x| ctx.constant(x).to_u32().unwrap())
+            .map(|x| ctx.constant(x).unwrap().to_u32().unwrap())
             .collect::<Vec<_>>();
-        ctx.select_by_indicator(a.as_slice(), indicator.as_slice());
+        ctx.select_by_indicator(a.as_slice(), indicator.as_slice())
+            .unwrap();
     }
 );
 
@@ -239,10 +241,10 @@ gate_test!(
         let a = inputs
             .0
             .iter()
-            .map(|x| ctx.constant(x).to_u32().unwrap())
+            .map(|x| ctx.constant(x).unwrap().to_u32().unwrap())
             .collect::<Vec<_>>();
-        let idx = ctx.constant(inputs.1);
-        ctx.select_from_idx(a.as_slice(), idx);
+        let idx = ctx.constant(inputs.1).unwrap();
+        ctx.select_from_idx(a.as_slice(), idx).unwrap();
     }
 );
 
@@ -255,8 +257,8 @@ gate_test!(
         chip.num_to_bits(ctx, num, num_bits);
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str, &str)| {
-        let num = ctx.constant(inputs.0);
-        ctx.num_to_bits(num, inputs.1);
+        let num = ctx.constant(inputs.0).unwrap();
+        ctx.num_to_bits(num, inputs.1).unwrap();
     }
 );
 
@@ -269,9 +271,9 @@ gate_test!(
         ctx.constrain_equal(&a, &b);
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str,)| {
-        let a = ctx.constant(inputs.0);
-        let b = ctx.constant(inputs.0);
-        ctx.constrain_equal(a, b);
+        let a = ctx.constant(inputs.0).unwrap();
+        let b = ctx.constant(inputs.0).unwrap();
+        ctx.constrain_equal(a, b).unwrap();
     }
 );
 
@@ -282,7 +284,7 @@ gate_test!(
         ctx.load_witness(Fr::from_str_vartime(inputs.0).unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str,)| {
-        ctx.witness(inputs.0);
+        ctx.witness(inputs.0).unwrap();
     }
 );
 
@@ -293,7 +295,7 @@ gate_test!(
         ctx.load_constant(Fr::from_str_vartime(inputs.0).unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str,)| {
-        ctx.constant(inputs.0);
+        ctx.constant(inputs.0).unwrap();
     }
 );
 
@@ -311,12 +313,12 @@ gate_test!(
         hasher.hash_fix_len_array(ctx, chip, &inputs);
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let inputs = inputs.iter().map(|x| ctx.constant(x)).collect::<Vec<_>>();
+        let inputs = inputs.iter().map(|x| ctx.constant(x).unwrap()).collect::<Vec<_>>();
         let inputs = inputs
             .iter()
             .map(|x| x.to_u32().unwrap())
             .collect::<Vec<_>>();
-        ctx.poseidon(inputs.as_slice());
+        ctx.poseidon(inputs.as_slice(), None).unwrap();
     }
 );
 
@@ -327,8 +329,8 @@ pub fn test_make_public() {
         make_public.push(a);
     });
     let wasm = base_test().run_wasm_builder_with_instances(|ctx, halo2_wasm| {
-        let a = ctx.constant("10");
-        ctx.make_public(halo2_wasm, a, 0);
+        let a = ctx.constant("10").unwrap();
+        ctx.make_public(halo2_wasm, a, 0).unwrap();
     });
     assert_eq!(base, wasm);
 }
@@ -0,0 +1,125 @@
+use super::{assert_digest, hex, witness_bytes};
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::{get_testing_circuit_config, mock_check_fitted};
+use crate::Halo2Wasm;
+
+/// Reference keccak256 on 64-bit lanes, deriving the round constants and rotations as in the
+/// specification rather than from tables.
+fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len() + 136 - bytes.len() % 136, 0);
+    padded[bytes.len()] |= 0x01;
+    *padded.last_mut().unwrap() |= 0x80;
+    let mut state = [0u64; 25];
+    for block in padded.chunks(136) {
+        for (lane, word) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(word.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+    let digest = state[..4].iter().flat_map(|lane| lane.to_le_bytes());
+    digest.collect::<Vec<_>>().try_into().unwrap()
+}
+
+fn keccak_f(a: &mut [u64; 25]) {
+    let mut rho = [0; 25];
+    let (mut x, mut y) = (1, 0);
+    for t in 0..24 {
+        rho[x + 5 * y] = ((t + 1) * (t + 2) / 2) % 64;
+        (x, y) = (y, (2 * x + 3 * y) % 5);
+    }
+    let mut lfsr = 1u8;
+    for _ in 0..24 {
+        let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |c, y| c ^ a[x + 5 * y]));
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotate_left(rho[x + 5 * y]);
+            }
+        }
+        for x in 0..5 {
+            for y in 0..5 {
+                a[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        for j in 0..7 {
+            if lfsr & 1 == 1 {
+                a[0] ^= 1 << ((1 << j) - 1);
+            }
+            lfsr = if lfsr & 0x80 != 0 {
+                (lfsr << 1) ^ 0x71
+            } else {
+                lfsr << 1
+            };
+        }
+    }
+}
+
+#[test]
+pub fn test_keccak256_reference() {
+    assert_eq!(
+        hex(&keccak256(b"")),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+    assert_eq!(
+        hex(&keccak256(b"abc")),
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+    );
+}
+
+#[test]
+pub fn test_keccak256() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    // one block, and two blocks where the padding fills the second one
+    let long = (0..136).map(|i| i as u8).collect::<Vec<_>>();
+    for input in [b"abc".as_slice(), long.as_slice()] {
+        let bytes = witness_bytes(&mut halo2libwasm, input);
+        let out = halo2libwasm.keccak256(&bytes).unwrap();
+        assert_digest(&mut halo2libwasm, out, keccak256(input));
+    }
+}
+
+#[test]
+pub fn test_keccak256_var_len() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    let input = (0..140).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+    let bytes = witness_bytes(&mut halo2libwasm, &input);
+    for len in [0, 136] {
+        let len_value = halo2libwasm.witness(&len.to_string()).unwrap();
+        let out = halo2libwasm.keccak256_var_len(&bytes, len_value).unwrap();
+        assert_digest(&mut halo2libwasm, out, keccak256(&input[..len]));
+    }
+}
+
+#[test]
+pub fn test_keccak256_mock_check() {
+    let input = b"abcd";
+    // `len` can be at most the number of bytes
+    for (len, satisfied) in [(3, true), (4, true), (5, false)] {
+        let circuit = |halo2libwasm: &mut Halo2LibWasm| {
+            let bytes = witness_bytes(halo2libwasm, input);
+            let len_value = halo2libwasm.witness(&len.to_string()).unwrap();
+            let out = halo2libwasm.keccak256_var_len(&bytes, len_value).unwrap();
+            if len <= input.len() {
+                assert_digest(halo2libwasm, out, keccak256(&input[..len]));
+            }
+        };
+        assert_eq!(mock_check_fitted(circuit), satisfied);
+    }
+    assert!(mock_check_fitted(|halo2libwasm| {
+        let bytes = witness_bytes(halo2libwasm, b"abc");
+        halo2libwasm.keccak256(&bytes).unwrap();
+    }));
+}
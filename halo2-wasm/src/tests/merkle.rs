@@ -0,0 +1,91 @@
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::halo2lib::merkle::MerkleTree;
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::get_testing_circuit_config;
+use crate::utils::fr_to_hex;
+use crate::{Halo2Wasm, Halo2WasmError};
+
+const LEAVES: [u64; 5] = [3, 1, 4, 1, 5];
+
+/// Witnesses the leaf, siblings and path bits of leaf `index`.
+fn witness_path(
+    halo2libwasm: &mut Halo2LibWasm,
+    tree: &MerkleTree,
+    index: usize,
+) -> (usize, Vec<u32>, Vec<u32>) {
+    let leaf = halo2libwasm.witness(&LEAVES[index].to_string()).unwrap();
+    let (siblings, path_bits) = tree.path(index).unwrap();
+    let siblings = siblings
+        .iter()
+        .map(|x| halo2libwasm.witness(&fr_to_hex(x)).unwrap() as u32)
+        .collect();
+    let path_bits = path_bits
+        .iter()
+        .map(|x| halo2libwasm.witness(&x.to_string()).unwrap() as u32)
+        .collect();
+    (leaf, siblings, path_bits)
+}
+
+#[test]
+pub fn test_merkle_root_from_path() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let tree = MerkleTree::from_leaves(LEAVES.map(Fr::from).to_vec(), None).unwrap();
+    assert_eq!(tree.depth(), 3);
+
+    for index in [0, 3, 4] {
+        let (leaf, siblings, path_bits) = witness_path(&mut halo2libwasm, &tree, index);
+        let root = halo2libwasm
+            .merkle_root_from_path(leaf, &siblings, &path_bits, None)
+            .unwrap();
+        assert_eq!(halo2libwasm.value(root).unwrap(), tree.root());
+    }
+}
+
+#[test]
+pub fn test_merkle_verify() {
+    // the value of leaf 0 does not verify with the path of leaf 1
+    for (leaf, satisfied) in [("1", true), ("3", false)] {
+        let mut halo2wasm = Halo2Wasm::default();
+        halo2wasm.config(get_testing_circuit_config()).unwrap();
+        let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+        let tree =
+            MerkleTree::from_leaves(LEAVES[..4].iter().map(|x| Fr::from(*x)).collect(), None)
+                .unwrap();
+
+        let (_, siblings, path_bits) = witness_path(&mut halo2libwasm, &tree, 1);
+        let leaf = halo2libwasm.witness(leaf).unwrap();
+        let root = halo2libwasm.witness(&tree.root()).unwrap();
+        halo2libwasm
+            .merkle_verify(root, leaf, &siblings, &path_bits, None)
+            .unwrap();
+        assert_eq!(halo2wasm.mock_check().unwrap().satisfied, satisfied);
+    }
+}
+
+#[test]
+pub fn test_merkle_tree() {
+    let tree = MerkleTree::from_leaves(vec![Fr::from(7)], None).unwrap();
+    assert_eq!(tree.depth(), 0);
+    assert_eq!(tree.root_value(), Fr::from(7));
+    assert_eq!(tree.path(0).unwrap(), (vec![], vec![]));
+
+    let tree = MerkleTree::from_leaves(LEAVES.map(Fr::from).to_vec(), None).unwrap();
+    let (siblings, path_bits) = tree.path(4).unwrap();
+    assert_eq!(siblings[0], Fr::from(0));
+    assert_eq!(path_bits, vec![0, 0, 1]);
+    // padding leaves have no paths
+    assert!(matches!(
+        tree.path(5),
+        Err(Halo2WasmError::LeafOutOfRange {
+            index: 5,
+            num_leaves: 5
+        })
+    ));
+    assert!(matches!(
+        MerkleTree::from_leaves(vec![], None),
+        Err(Halo2WasmError::NoLeaves)
+    ));
+}
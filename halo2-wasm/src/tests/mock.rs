@@ -0,0 +1,158 @@
+use halo2_base::halo2_proofs::{halo2curves::bn256::Fr, plonk::Assigned};
+
+use crate::halo2lib::Halo2LibWasm;
+use crate::mock::MockFailureKind;
+use crate::tests::utils::get_testing_circuit_config;
+use crate::utils::fr_to_hex;
+use crate::Halo2Wasm;
+
+fn setup() -> (Halo2Wasm, Halo2LibWasm) {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    (halo2wasm, halo2libwasm)
+}
+
+#[test]
+pub fn test_mock_check_satisfied() {
+    let (mut halo2wasm, mut halo2libwasm) = setup();
+    let a = halo2libwasm.witness("1").unwrap();
+    halo2libwasm.assert_is_const(a, "1").unwrap();
+    halo2libwasm.range_check(a, "64").unwrap();
+    halo2wasm.assign_instances();
+
+    let result = halo2wasm.mock_check().unwrap();
+    assert!(result.satisfied);
+    assert!(result.failures.is_empty());
+}
+
+#[test]
+pub fn test_mock_check_constant_mismatch() {
+    let (mut halo2wasm, mut halo2libwasm) = setup();
+    let a = halo2libwasm.witness("1").unwrap();
+    halo2libwasm.assert_is_const(a, "2").unwrap();
+    halo2wasm.assign_instances();
+
+    let result = halo2wasm.mock_check().unwrap();
+    assert!(!result.satisfied);
+    // the copy of `a` to the constant fails at the cell of `a`
+    let a = halo2libwasm.assigned_value(a).unwrap().cell.unwrap();
+    assert!(result
+        .failures
+        .iter()
+        .filter(|f| f.kind == MockFailureKind::Permutation)
+        .flat_map(|f| &f.cells)
+        .any(|cell| (cell.thread, cell.offset) == (a.context_id, a.offset)));
+    assert!(!halo2wasm.mock_failures().unwrap().is_empty());
+}
+
+#[test]
+pub fn test_mock_check_lookup_failure() {
+    let (mut halo2wasm, mut halo2libwasm) = setup();
+    // 2^64 does not fit in 64 bits: its most significant 9-bit limb is 2, which is shifted by
+    // 2^8 to check it has a single bit, giving 2^9 outside the lookup table.
+    let a = halo2libwasm.witness("18446744073709551616").unwrap();
+    halo2libwasm.range_check(a, "64").unwrap();
+    // in range values are looked up in other rows and columns
+    for i in 0..10 {
+        let b = halo2libwasm.witness(&i.to_string()).unwrap();
+        halo2libwasm.range_check(b, "64").unwrap();
+    }
+    halo2wasm.assign_instances();
+
+    let result = halo2wasm.mock_check().unwrap();
+    assert!(!result.satisfied);
+    let failures = result
+        .failures
+        .iter()
+        .filter(|f| f.kind == MockFailureKind::Lookup)
+        .collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    let [input] = failures[0].cells.as_slice() else {
+        panic!("expected a single looked up cell");
+    };
+    assert_eq!(input.thread, 0);
+    assert_eq!(input.value, fr_to_hex(&Fr::from(512)));
+}
+
+#[test]
+pub fn test_mock_check_constraint_failure() {
+    let (mut halo2wasm, mut halo2libwasm) = setup();
+    halo2libwasm.enable_debug();
+    halo2libwasm.set_debug_label(Some("circuit.ts:7".to_string()));
+    let a = halo2libwasm.witness("1").unwrap();
+    let b = halo2libwasm.witness("2").unwrap();
+    let c = halo2libwasm.add(a, b).unwrap();
+    // overwrite the output so `a + b * 1 = c` no longer holds
+    let c = halo2libwasm.assigned_value(c).unwrap().cell.unwrap();
+    halo2wasm.circuit.borrow_mut().main(0).advice[c.offset] = Assigned::Trivial(Fr::from(4));
+    halo2wasm.assign_instances();
+
+    let result = halo2wasm.mock_check().unwrap();
+    let result = halo2libwasm.annotate_mock_check(result);
+    let failure = result
+        .failures
+        .iter()
+        .find(|f| f.kind == MockFailureKind::Constraint)
+        .unwrap();
+    assert_eq!(failure.cells.len(), 4);
+    assert!(failure
+        .cells
+        .iter()
+        .any(|cell| cell.offset == c.offset && cell.value == fr_to_hex(&Fr::from(4))));
+    for cell in &failure.cells {
+        assert_eq!(cell.thread, 0);
+        assert_eq!(cell.source.as_deref(), Some("add at circuit.ts:7"));
+    }
+}
+
+#[test]
+pub fn test_mock_check_debug_sources() {
+    let (mut halo2wasm, mut halo2libwasm) = setup();
+    halo2libwasm.enable_debug();
+    halo2libwasm.set_debug_label(Some("circuit.ts:1".to_string()));
+    let a = halo2libwasm.witness("18446744073709551616").unwrap();
+    let b = halo2libwasm.witness("1").unwrap();
+    let c = halo2libwasm.add(a, b).unwrap();
+    halo2libwasm.set_debug_label(Some("circuit.ts:42".to_string()));
+    halo2libwasm.range_check(a, "64").unwrap();
+    halo2wasm.assign_instances();
+
+    assert_eq!(halo2libwasm.debug_op(b).unwrap(), "witness at circuit.ts:1");
+    assert_eq!(halo2libwasm.debug_op(c).unwrap(), "add at circuit.ts:1");
+
+    let result = halo2wasm.mock_check().unwrap();
+    let result = halo2libwasm.annotate_mock_check(result);
+    let failure = result
+        .failures
+        .iter()
+        .find(|f| f.kind == MockFailureKind::Lookup)
+        .unwrap();
+    assert_eq!(failure.cells.len(), 1);
+    assert_eq!(
+        failure.cells[0].source.as_deref(),
+        Some("range_check at circuit.ts:42")
+    );
+}
+
+#[test]
+pub fn test_debug_trace_cleared_with_circuit() {
+    let (mut halo2wasm, mut halo2libwasm) = setup();
+    halo2libwasm.enable_debug();
+    halo2libwasm.witness("1").unwrap();
+    halo2libwasm.new_thread().unwrap();
+    let a = halo2libwasm.witness("2").unwrap();
+    assert_eq!(halo2libwasm.debug_op(a).unwrap(), "witness");
+    // rejected inputs leave the trace untouched
+    assert!(halo2libwasm.witness("x").is_err());
+    assert_eq!(halo2libwasm.debug_op(a).unwrap(), "witness");
+
+    halo2wasm.clear().unwrap();
+    halo2libwasm.config().unwrap();
+    let b = halo2libwasm.constant("3").unwrap();
+    // thread 1 is assigned again, but outside of any op
+    let c = halo2libwasm.parallelize(vec![4u64], |ctx, _, x| ctx.load_witness(Fr::from(x)));
+    let c = halo2libwasm.to_js_assigned_value(c[0]).unwrap();
+    assert_eq!(halo2libwasm.debug_op(b).unwrap(), "constant");
+    assert_eq!(halo2libwasm.debug_op(c), None);
+}
@@ -1,3 +1,39 @@
+use crate::halo2lib::ecc::JsCircuitValue256;
+use crate::halo2lib::Halo2LibWasm;
+use crate::utils::{fr_from_bytes_be, fr_to_hex};
+
+mod aggregation;
+mod artifact;
+mod batch;
+mod config;
+mod encoding;
+mod evm;
 mod gate;
+mod keccak;
+mod merkle;
+mod mock;
+mod poseidon;
 mod range;
-mod utils;
\ No newline at end of file
+mod sha256;
+mod snark;
+mod thread;
+mod utils;
+mod verify;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn witness_bytes(halo2libwasm: &mut Halo2LibWasm, bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .iter()
+        .map(|x| halo2libwasm.witness(&x.to_string()).unwrap() as u32)
+        .collect()
+}
+
+/// Asserts `out` holds the hi and lo 128 bits of `digest`.
+fn assert_digest(halo2libwasm: &mut Halo2LibWasm, out: JsCircuitValue256, digest: [u8; 32]) {
+    let [hi, lo] = [&digest[..16], &digest[16..]].map(|x| fr_to_hex(&fr_from_bytes_be(x).unwrap()));
+    assert_eq!(halo2libwasm.value(out.hi).unwrap(), hi);
+    assert_eq!(halo2libwasm.value(out.lo).unwrap(), lo);
+}
@@ -0,0 +1,95 @@
+use halo2_base::gates::RangeInstructions;
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_base::poseidon::hasher::{spec::OptimizedPoseidonSpec, PoseidonHasher};
+
+use crate::halo2lib::poseidon::{poseidon_native, PoseidonSpec};
+use crate::halo2lib::{Halo2LibWasm, R_F, SECURE_MDS};
+use crate::tests::utils::{base_test, get_testing_circuit_config};
+use crate::Halo2Wasm;
+
+const INPUTS: [u64; 5] = [90, 50, 12, 12, 7];
+
+#[test]
+pub fn test_poseidon_spec() {
+    let base = base_test().run(|ctx, range| {
+        let inputs = INPUTS.map(|x| ctx.load_constant(Fr::from(x)));
+        let spec = OptimizedPoseidonSpec::<Fr, 5, 4>::new::<R_F, 60, SECURE_MDS>();
+        let mut hasher = PoseidonHasher::new(spec);
+        hasher.initialize_consts(ctx, range.gate());
+        // constants are assigned once for both hashes
+        hasher.hash_fix_len_array(ctx, range.gate(), &inputs);
+        hasher.hash_fix_len_array(ctx, range.gate(), &inputs[..2]);
+    });
+    let wasm = base_test().run_wasm_builder(|ctx| {
+        let inputs = INPUTS.map(|x| ctx.constant(&x.to_string()).unwrap() as u32);
+        ctx.poseidon(&inputs, Some(PoseidonSpec::T5)).unwrap();
+        ctx.poseidon(&inputs[..2], Some(PoseidonSpec::T5)).unwrap();
+    });
+    assert_eq!(base, wasm);
+}
+
+#[test]
+pub fn test_poseidon_var_len() {
+    let base = base_test().run(|ctx, range| {
+        let inputs = INPUTS.map(|x| ctx.load_constant(Fr::from(x)));
+        let len = ctx.load_witness(Fr::from(3));
+        let spec = OptimizedPoseidonSpec::<Fr, 3, 2>::new::<R_F, 57, SECURE_MDS>();
+        let mut hasher = PoseidonHasher::new(spec);
+        hasher.initialize_consts(ctx, range.gate());
+        hasher.hash_var_len_array(ctx, range, &inputs, len);
+    });
+    let wasm = base_test().run_wasm_builder(|ctx| {
+        let inputs = INPUTS.map(|x| ctx.constant(&x.to_string()).unwrap() as u32);
+        let len = ctx.witness("3").unwrap();
+        ctx.poseidon_var_len(&inputs, len, None).unwrap();
+    });
+    assert_eq!(base, wasm);
+}
+
+#[test]
+pub fn test_poseidon_var_len_matches_fixed() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    let inputs = INPUTS.map(|x| halo2libwasm.witness(&x.to_string()).unwrap() as u32);
+    for spec in [PoseidonSpec::T3, PoseidonSpec::T9] {
+        for len in 0..=INPUTS.len() {
+            let var_len = halo2libwasm.witness(&len.to_string()).unwrap();
+            let var_len = halo2libwasm
+                .poseidon_var_len(&inputs, var_len, Some(spec))
+                .unwrap();
+            let fixed = halo2libwasm.poseidon(&inputs[..len], Some(spec)).unwrap();
+            assert_eq!(
+                halo2libwasm.value(var_len).unwrap(),
+                halo2libwasm.value(fixed).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+pub fn test_poseidon_native() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    let values = INPUTS.map(|x| x.to_string());
+    let inputs = values
+        .clone()
+        .map(|x| halo2libwasm.witness(&x).unwrap() as u32);
+    for spec in [
+        PoseidonSpec::T3,
+        PoseidonSpec::T4,
+        PoseidonSpec::T5,
+        PoseidonSpec::T9,
+    ] {
+        for len in 0..=INPUTS.len() {
+            let hash = halo2libwasm.poseidon(&inputs[..len], Some(spec)).unwrap();
+            assert_eq!(
+                poseidon_native(values[..len].to_vec(), Some(spec)).unwrap(),
+                halo2libwasm.value(hash).unwrap()
+            );
+        }
+    }
+}
@@ -31,8 +31,8 @@ range_test!(
         chip.range_check(ctx, a, inputs.1);
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str, usize)| {
-        let a = ctx.witness(inputs.0);
-        ctx.range_check(a, &inputs.1.to_string())
+        let a = ctx.witness(inputs.0).unwrap();
+        ctx.range_check(a, &inputs.1.to_string()).unwrap()
     }
 );
 
@@ -49,11 +49,11 @@ range_test!(
         chip.check_less_than(ctx, a, b, inputs.2);
     },
     |ctx: &mut Halo2LibWasm, inputs: (&str, &str, usize)| {
-        let a = ctx.witness(inputs.0);
-        let b = ctx.witness(inputs.1);
-        ctx.range_check(a, &inputs.2.to_string());
-        ctx.range_check(b, &inputs.2.to_string());
-        ctx.check_less_than(a, b, &inputs.2.to_string());
+        let a = ctx.witness(inputs.0).unwrap();
+        let b = ctx.witness(inputs.1).unwrap();
+        ctx.range_check(a, &inputs.2.to_string()).unwrap();
+        ctx.range_check(b, &inputs.2.to_string()).unwrap();
+        ctx.check_less_than(a, b, &inputs.2.to_string()).unwrap();
     }
 );
 
@@ -65,8 +65,8 @@ range_test!(
         chip.check_less_than_safe(ctx, a, inputs[1].parse().unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let a = ctx.witness(inputs[0]);
-        ctx.check_less_than_safe(a, inputs[1]);
+        let a = ctx.witness(inputs[0]).unwrap();
+        ctx.check_less_than_safe(a, inputs[1]).unwrap();
     }
 );
 
@@ -83,11 +83,11 @@ range_test!(
         chip.is_less_than(ctx, a, b, inputs[2].parse().unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let a = ctx.witness(inputs[0]);
-        let b = ctx.witness(inputs[1]);
-        ctx.range_check(a, inputs[2]);
-        ctx.range_check(b, inputs[2]);
-        ctx.is_less_than(a, b, inputs[2]);
+        let a = ctx.witness(inputs[0]).unwrap();
+        let b = ctx.witness(inputs[1]).unwrap();
+        ctx.range_check(a, inputs[2]).unwrap();
+        ctx.range_check(b, inputs[2]).unwrap();
+        ctx.is_less_than(a, b, inputs[2]).unwrap();
     }
 );
 
@@ -99,8 +99,8 @@ range_test!(
         chip.is_less_than_safe(ctx, a, inputs[1].parse().unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let a = ctx.witness(inputs[0]);
-        ctx.is_less_than_safe(a, inputs[1]);
+        let a = ctx.witness(inputs[0]).unwrap();
+        ctx.is_less_than_safe(a, inputs[1]).unwrap();
     }
 );
 
@@ -113,8 +113,8 @@ range_test!(
         chip.div_mod(ctx, a, b, inputs[2].parse().unwrap());
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let a = ctx.witness(inputs[0]);
-        ctx.div_mod(a, inputs[1], inputs[2]);
+        let a = ctx.witness(inputs[0]).unwrap();
+        ctx.div_mod(a, inputs[1], inputs[2]).unwrap();
     }
 );
 
@@ -133,8 +133,8 @@ range_test!(
         );
     },
     |ctx: &mut Halo2LibWasm, inputs: &[&str]| {
-        let a = ctx.witness(inputs[0]);
-        let b = ctx.witness(inputs[1]);
-        ctx.div_mod_var(a, b, inputs[2], inputs[3]);
+        let a = ctx.witness(inputs[0]).unwrap();
+        let b = ctx.witness(inputs[1]).unwrap();
+        ctx.div_mod_var(a, b, inputs[2], inputs[3]).unwrap();
     }
 );
@@ -0,0 +1,97 @@
+use super::{assert_digest, hex, witness_bytes};
+use crate::halo2lib::sha256::{INITIAL_STATE, ROUND_CONSTANTS};
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::{get_testing_circuit_config, mock_check_fitted};
+use crate::Halo2Wasm;
+
+/// Reference SHA-256 on 32-bit words.
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend((8 * bytes.len() as u64).to_be_bytes());
+    let mut state = INITIAL_STATE;
+    for block in padded.chunks(64) {
+        let mut w = block
+            .chunks(4)
+            .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        for t in 16..64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w.push(
+                s1.wrapping_add(w[t - 7])
+                    .wrapping_add(s0)
+                    .wrapping_add(w[t - 16]),
+            );
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (w, k) in w.iter().zip(ROUND_CONSTANTS) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(k)
+                .wrapping_add(*w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+        }
+        for (x, y) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+    let digest = state.iter().flat_map(|word| word.to_be_bytes());
+    digest.collect::<Vec<_>>().try_into().unwrap()
+}
+
+#[test]
+pub fn test_sha256_reference() {
+    assert_eq!(
+        hex(&sha256(b"")),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        hex(&sha256(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        hex(&sha256(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+        )),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+}
+
+#[test]
+pub fn test_sha256() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    // one block, and two blocks where the length no longer fits in the first one
+    let inputs: [&[u8]; 3] = [
+        b"",
+        b"abc",
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+    ];
+    for input in inputs {
+        let bytes = witness_bytes(&mut halo2libwasm, input);
+        let out = halo2libwasm.sha256(&bytes).unwrap();
+        assert_digest(&mut halo2libwasm, out, sha256(input));
+    }
+}
+
+#[test]
+pub fn test_sha256_mock_check() {
+    assert!(mock_check_fitted(|halo2libwasm| {
+        let input = b"abc";
+        let bytes = witness_bytes(halo2libwasm, input);
+        let out = halo2libwasm.sha256(&bytes).unwrap();
+        assert_digest(halo2libwasm, out, sha256(input));
+    }));
+}
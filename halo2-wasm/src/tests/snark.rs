@@ -0,0 +1,75 @@
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::snark::{load_snark, load_snark_artifact, SNARK_MAGIC};
+use crate::tests::utils::prove_sum;
+use crate::transcript::TranscriptType;
+use crate::verifier::Halo2Verifier;
+use crate::Halo2WasmError;
+
+#[test]
+pub fn test_prove_snark_roundtrip() {
+    let (halo2wasm, _) = prove_sum("10", "15");
+    let bytes = halo2wasm.prove_snark().unwrap();
+    assert_eq!(bytes[..4], SNARK_MAGIC);
+
+    let snark = load_snark(&bytes).unwrap();
+    assert_eq!(snark.instances, vec![vec![Fr::from(25)]]);
+    assert_eq!(snark.protocol.num_instance, vec![1]);
+    assert!(halo2wasm
+        .verify_proof_with_instances(&snark.proof, &snark.instances)
+        .unwrap());
+}
+
+#[test]
+pub fn test_load_snark_rejects_bad_header() {
+    let (halo2wasm, _) = prove_sum("10", "15");
+    let mut bytes = halo2wasm.prove_snark().unwrap();
+    bytes[4] = 0xff;
+    assert!(matches!(
+        load_snark(&bytes),
+        Err(Halo2WasmError::Deserialize(_))
+    ));
+}
+
+#[test]
+pub fn test_prove_with_transcripts() {
+    let (mut halo2wasm, _) = prove_sum("10", "15");
+    let instances = [vec![Fr::from(25)]];
+    for transcript in [TranscriptType::Evm, TranscriptType::Blake2b] {
+        halo2wasm.set_transcript(transcript);
+        let proof = halo2wasm.prove().unwrap();
+        assert!(halo2wasm
+            .verify_proof_with_instances(&proof, &instances)
+            .unwrap());
+
+        halo2wasm.set_transcript(TranscriptType::Poseidon);
+        assert!(!halo2wasm
+            .verify_proof_with_instances(&proof, &instances)
+            .unwrap());
+    }
+}
+
+#[test]
+pub fn test_snark_records_transcript() {
+    let (mut halo2wasm, _) = prove_sum("10", "15");
+    halo2wasm.set_transcript(TranscriptType::Evm);
+    let bytes = halo2wasm.prove_snark().unwrap();
+
+    let artifact = load_snark_artifact(&bytes).unwrap();
+    assert_eq!(artifact.transcript, TranscriptType::Evm);
+    assert_eq!(
+        load_snark(&bytes).err(),
+        Some(Halo2WasmError::TranscriptMismatch {
+            expected: TranscriptType::Poseidon,
+            found: TranscriptType::Evm,
+        })
+    );
+
+    let verifier = Halo2Verifier::new(
+        &halo2wasm.get_circuit_params().unwrap(),
+        &halo2wasm.get_vk().unwrap(),
+        &halo2wasm.get_verifier_params().unwrap(),
+    )
+    .unwrap();
+    assert!(verifier.verify_snark(&bytes).unwrap());
+}
@@ -0,0 +1,185 @@
+use halo2_base::{
+    gates::{GateInstructions, RangeInstructions},
+    halo2_proofs::halo2curves::bn256::Fr,
+};
+
+use crate::halo2lib::{
+    batch::BATCH_OUTPUT_FLAG,
+    handles::{encode_handle, MAX_HANDLES},
+    Halo2LibWasm,
+};
+use crate::tests::utils::get_testing_circuit_config;
+use crate::utils::fr_to_hex;
+use crate::{Halo2Wasm, Halo2WasmError};
+
+#[test]
+pub fn test_threads() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    let a = halo2libwasm.witness("3").unwrap();
+    assert_eq!(halo2libwasm.thread(), 0);
+    assert_eq!(halo2libwasm.new_thread().unwrap(), 1);
+    assert_eq!(halo2libwasm.num_threads(), 2);
+    assert!(matches!(
+        halo2libwasm.set_thread(2),
+        Err(Halo2WasmError::ThreadOutOfRange {
+            thread: 2,
+            num_threads: 2
+        })
+    ));
+    assert_eq!(halo2libwasm.thread(), 1);
+
+    // values from the first thread can be used in the second one
+    let b = halo2libwasm.witness("4").unwrap();
+    assert_ne!(a, b);
+    let c = halo2libwasm.mul(a, b).unwrap();
+    halo2libwasm.range_check(c, "8").unwrap();
+    assert_eq!(halo2libwasm.value(c).unwrap(), fr_to_hex(&Fr::from(12)));
+
+    halo2libwasm.set_thread(0).unwrap();
+    let d = halo2libwasm.add(a, c).unwrap();
+    assert_eq!(halo2libwasm.value(d).unwrap(), fr_to_hex(&Fr::from(15)));
+    halo2libwasm.make_public(&mut halo2wasm, d, 0).unwrap();
+
+    halo2wasm.assign_instances();
+    halo2wasm.mock().unwrap();
+    assert_eq!(halo2wasm.get_instances(0).unwrap(), vec![d as u32]);
+}
+
+#[test]
+pub fn test_thread_reset_on_clear() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    halo2libwasm.witness("1").unwrap();
+    halo2libwasm.new_thread().unwrap();
+    halo2libwasm.new_thread().unwrap();
+    halo2libwasm.set_thread(1).unwrap();
+
+    // the selected thread is gone, so ops are assigned in a new first thread
+    halo2wasm.clear().unwrap();
+    let a = halo2libwasm.witness("2").unwrap();
+    assert_eq!(halo2libwasm.thread(), 0);
+    assert_eq!(halo2libwasm.num_threads(), 1);
+    assert_eq!(halo2libwasm.value(a).unwrap(), fr_to_hex(&Fr::from(2)));
+}
+
+#[test]
+pub fn test_parallelize() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+
+    let one = halo2libwasm.constant("1").unwrap();
+    let squares = halo2libwasm.parallelize((1..=8u64).collect(), |ctx, range, x| {
+        let x = ctx.load_witness(Fr::from(x));
+        range.range_check(ctx, x, 8);
+        range.gate().mul(ctx, x, x)
+    });
+    assert_eq!(halo2libwasm.num_threads(), 9);
+    assert_eq!(halo2libwasm.thread(), 0);
+
+    let squares = squares
+        .into_iter()
+        .map(|x| halo2libwasm.to_js_assigned_value(x).unwrap() as u32)
+        .collect::<Vec<_>>();
+    let sum = halo2libwasm.sum(&squares).unwrap();
+    let sum = halo2libwasm.add(sum, one).unwrap();
+    assert_eq!(halo2libwasm.value(sum).unwrap(), fr_to_hex(&Fr::from(205)));
+    halo2libwasm.make_public(&mut halo2wasm, sum, 0).unwrap();
+
+    halo2wasm.assign_instances();
+    halo2wasm.mock().unwrap();
+}
+
+#[test]
+pub fn test_stale_handles() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let a = halo2libwasm.witness("1").unwrap();
+    assert_eq!(
+        halo2libwasm.assigned_value(a).unwrap().value(),
+        &Fr::from(1)
+    );
+
+    // handles of another circuit are rejected
+    let mut other = Halo2Wasm::default();
+    other.config(get_testing_circuit_config()).unwrap();
+    let b = Halo2LibWasm::new(&other).unwrap().witness("2").unwrap();
+    assert!(matches!(
+        halo2libwasm.assigned_value(b),
+        Err(Halo2WasmError::StaleHandle(_))
+    ));
+
+    // so are handles from before a clear, although their cell is assigned again
+    halo2wasm.clear().unwrap();
+    halo2libwasm.config().unwrap();
+    let c = halo2libwasm.witness("3").unwrap();
+    assert_ne!(a, c);
+    assert!(matches!(
+        halo2libwasm.assigned_value(a),
+        Err(Halo2WasmError::StaleHandle(_))
+    ));
+    assert_eq!(
+        halo2libwasm.assigned_value(c).unwrap().value(),
+        &Fr::from(3)
+    );
+    // as are handles that were never returned
+    assert!(matches!(
+        halo2libwasm.assigned_value(c + 1),
+        Err(Halo2WasmError::StaleHandle(_))
+    ));
+}
+
+#[test]
+pub fn test_stale_handles_after_many_clears() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let mut handles = vec![halo2libwasm.witness("0").unwrap()];
+    for i in 1..=40 {
+        halo2wasm.clear().unwrap();
+        halo2libwasm.config().unwrap();
+        let a = halo2libwasm.witness(&i.to_string()).unwrap();
+        assert!(!handles.contains(&a));
+        for &stale in &handles {
+            assert!(matches!(
+                halo2libwasm.assigned_value(stale),
+                Err(Halo2WasmError::StaleHandle(_))
+            ));
+        }
+        assert_eq!(halo2libwasm.value(a).unwrap(), fr_to_hex(&Fr::from(i)));
+        handles.push(a);
+    }
+}
+
+#[test]
+pub fn test_handles_after_many_clears() {
+    let mut halo2wasm = Halo2Wasm::default();
+    halo2wasm.config(get_testing_circuit_config()).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    // more clears than there are tags, which wrap around instead of running out
+    for i in 0..300 {
+        halo2wasm.clear().unwrap();
+        halo2libwasm.config().unwrap();
+        let a = halo2libwasm.witness(&i.to_string()).unwrap();
+        assert!(a < BATCH_OUTPUT_FLAG as usize);
+        assert_eq!(halo2libwasm.value(a).unwrap(), fr_to_hex(&Fr::from(i)));
+    }
+}
+
+#[test]
+pub fn test_too_many_cells() {
+    let last_tag = (BATCH_OUTPUT_FLAG as usize / MAX_HANDLES - 1) as u32;
+    assert_eq!(
+        encode_handle(last_tag, MAX_HANDLES - 1),
+        Ok(BATCH_OUTPUT_FLAG as usize - 1)
+    );
+    assert_eq!(
+        encode_handle(last_tag, MAX_HANDLES),
+        Err(Halo2WasmError::TooManyCells(MAX_HANDLES))
+    );
+}
@@ -28,6 +28,67 @@ pub fn get_testing_config() -> BaseCircuitParams {
     }
 }
 
+pub fn get_testing_circuit_config() -> CircuitConfig {
+    let config = get_testing_config();
+    CircuitConfig {
+        k: config.k,
+        num_advice: config.num_advice_per_phase[0],
+        num_lookup_advice: config.num_lookup_advice_per_phase[0],
+        num_instance: config.num_instance_columns,
+        num_lookup_bits: config.lookup_bits.unwrap(),
+        num_virtual_instance: 1,
+        num_fixed: None,
+        instance_columns: None,
+    }
+}
+
+pub fn get_testing_params(k: usize) -> Vec<u8> {
+    let params = gen_srs(k.try_into().unwrap());
+    let mut buffer: Vec<u8> = Vec::new();
+    params.write(&mut buffer).unwrap();
+    buffer
+}
+
+pub fn load_testing_params(halo2wasm: &mut Halo2Wasm, k: usize) {
+    halo2wasm.load_params(&get_testing_params(k)).unwrap();
+}
+
+/// Proves `a + b` with the sum exposed as the only public output.
+pub fn prove_sum(a: &str, b: &str) -> (Halo2Wasm, Vec<u8>) {
+    let mut halo2wasm = Halo2Wasm::default();
+    let config = get_testing_circuit_config();
+    let k = config.k;
+    halo2wasm.config(config).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    let a = halo2libwasm.witness(a).unwrap();
+    let b = halo2libwasm.witness(b).unwrap();
+    let c = halo2libwasm.add(a, b).unwrap();
+    halo2libwasm.make_public(&mut halo2wasm, c, 0).unwrap();
+    halo2wasm.assign_instances();
+
+    load_testing_params(&mut halo2wasm, k);
+    halo2wasm.gen_vk().unwrap();
+    halo2wasm.gen_pk().unwrap();
+    let proof = halo2wasm.prove().unwrap();
+    (halo2wasm, proof)
+}
+
+/// Runs `f` again in the config `autoConfig` fits to it, for circuits too large for the testing
+/// config, and returns whether the mock prover is satisfied.
+pub fn mock_check_fitted(f: impl Fn(&mut Halo2LibWasm)) -> bool {
+    let mut halo2wasm = Halo2Wasm::default();
+    let config = get_testing_circuit_config();
+    let (k, lookup_bits) = (config.k, config.num_lookup_bits);
+    halo2wasm.config(config).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    f(&mut halo2libwasm);
+    halo2wasm.auto_config(k, 20, lookup_bits, None).unwrap();
+    halo2libwasm.config().unwrap();
+    f(&mut halo2libwasm);
+    halo2wasm.assign_instances();
+    halo2wasm.mock_check().unwrap().satisfied
+}
+
 pub struct BaseTester {
     config: BaseCircuitParams,
 }
@@ -116,26 +177,15 @@ impl BaseTester {
     ) -> TestResult {
         let mut halo2wasm = Halo2Wasm::default();
 
-        let config = CircuitConfig {
-            k: self.config.k,
-            num_advice: self.config.num_advice_per_phase[0],
-            num_lookup_advice: self.config.num_lookup_advice_per_phase[0],
-            num_instance: self.config.num_instance_columns,
-            num_lookup_bits: self.config.lookup_bits.unwrap(),
-            num_virtual_instance: 1,
-        };
-        halo2wasm.config(config);
-        let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm);
+        halo2wasm.config(get_testing_circuit_config()).unwrap();
+        let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
 
         f(&mut halo2libwasm, &mut halo2wasm);
         halo2wasm.assign_instances();
 
-        let params = gen_srs(self.config.k.try_into().unwrap());
-        let mut buffer: Vec<u8> = Vec::new();
-        params.write(&mut buffer).unwrap();
-        halo2wasm.load_params(buffer.as_slice());
-        halo2wasm.gen_vk();
-        let vk = halo2wasm.get_vk();
+        load_testing_params(&mut halo2wasm, self.config.k);
+        halo2wasm.gen_vk().unwrap();
+        let vk = halo2wasm.get_vk().unwrap();
 
         TestResult { vk }
     }
@@ -0,0 +1,82 @@
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::halo2lib::Halo2LibWasm;
+use crate::tests::utils::{get_testing_circuit_config, load_testing_params, prove_sum};
+use crate::verifier::Halo2Verifier;
+use crate::{Halo2Wasm, Halo2WasmError};
+
+#[test]
+pub fn test_verify_proof_with_instances() {
+    let (halo2wasm, proof) = prove_sum("10", "15");
+    let valid = halo2wasm
+        .verify_proof_with_instances(&proof, &[vec![Fr::from(25)]])
+        .unwrap();
+    assert!(valid);
+    let invalid = halo2wasm
+        .verify_proof_with_instances(&proof, &[vec![Fr::from(26)]])
+        .unwrap();
+    assert!(!invalid);
+}
+
+#[test]
+pub fn test_verify_proof_instance_column_mismatch() {
+    let (halo2wasm, proof) = prove_sum("10", "15");
+    let res = halo2wasm.verify_proof_with_instances(&proof, &[vec![Fr::from(25)], vec![]]);
+    assert_eq!(
+        res,
+        Err(Halo2WasmError::InstanceColumnMismatch {
+            expected: 1,
+            found: 2
+        })
+    );
+}
+
+#[test]
+pub fn test_standalone_verifier() {
+    let (halo2wasm, proof) = prove_sum("10", "15");
+    let verifier = Halo2Verifier::new(
+        &halo2wasm.get_circuit_params().unwrap(),
+        &halo2wasm.get_vk().unwrap(),
+        &halo2wasm.get_verifier_params().unwrap(),
+    )
+    .unwrap();
+    assert!(verifier
+        .verify_with_instances(&proof, &[vec![Fr::from(25)]])
+        .unwrap());
+    assert!(!verifier
+        .verify_with_instances(&proof, &[vec![Fr::from(26)]])
+        .unwrap());
+}
+
+#[test]
+pub fn test_multiple_instance_columns() {
+    let mut halo2wasm = Halo2Wasm::default();
+    let mut config = get_testing_circuit_config();
+    let k = config.k;
+    config.num_instance = 2;
+    config.num_virtual_instance = 3;
+    config.instance_columns = Some(vec![1, 0, 1]);
+    halo2wasm.config(config).unwrap();
+    let mut halo2libwasm = Halo2LibWasm::new(&halo2wasm).unwrap();
+    for (value, virtual_col) in [5, 6, 7].into_iter().zip([0, 1, 2]) {
+        let a = halo2libwasm.witness(&value.to_string()).unwrap();
+        halo2libwasm
+            .make_public(&mut halo2wasm, a, virtual_col)
+            .unwrap();
+    }
+    halo2wasm.assign_instances();
+    halo2wasm.mock().unwrap();
+
+    load_testing_params(&mut halo2wasm, k);
+    halo2wasm.gen_vk().unwrap();
+    halo2wasm.gen_pk().unwrap();
+    let proof = halo2wasm.prove().unwrap();
+    let instances = [vec![Fr::from(6)], vec![Fr::from(5), Fr::from(7)]];
+    assert!(halo2wasm
+        .verify_proof_with_instances(&proof, &instances)
+        .unwrap());
+    let flattened = [vec![Fr::from(5), Fr::from(6), Fr::from(7)], vec![]];
+    assert!(!halo2wasm
+        .verify_proof_with_instances(&proof, &flattened)
+        .unwrap());
+}
@@ -0,0 +1,187 @@
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{verify_proof, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Challenge255, EncodedChallenge, TranscriptRead, TranscriptReadBuffer,
+    },
+};
+#[cfg(feature = "prover")]
+use halo2_base::halo2_proofs::{
+    plonk::{create_proof, Circuit, ProvingKey},
+    poly::kzg::multiopen::ProverSHPLONK,
+    transcript::{Blake2bWrite, TranscriptWrite, TranscriptWriterBuffer},
+};
+use itertools::Itertools;
+#[cfg(feature = "prover")]
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "evm", not(target_family = "wasm")))]
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+#[cfg(feature = "prover")]
+use snark_verifier::system::halo2::{compile, Config};
+use snark_verifier_sdk::{
+    halo2::{PoseidonTranscript, POSEIDON_SPEC},
+    NativeLoader,
+};
+#[cfg(feature = "prover")]
+use snark_verifier_sdk::{CircuitExt, Snark};
+use tsify::Tsify;
+
+use crate::Halo2WasmError;
+
+/// Fiat-Shamir transcript used to create and verify proofs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum TranscriptType {
+    /// Cheap to verify in a circuit, required for aggregation.
+    #[default]
+    Poseidon,
+    /// Keccak transcript expected by the Solidity verifier. Only supported by wasm builds with
+    /// the `evm` feature, which the published packages enable.
+    Evm,
+    /// The default transcript of `halo2_proofs`.
+    Blake2b,
+}
+
+/// Proves `circuit` with the given transcript, returning the proof with its protocol and
+/// instances.
+#[cfg(feature = "prover")]
+pub(crate) fn gen_snark<C: CircuitExt<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    transcript: TranscriptType,
+) -> Result<Snark, Halo2WasmError> {
+    let protocol = compile(
+        params,
+        pk.get_vk(),
+        Config::kzg()
+            .with_num_instance(circuit.num_instance())
+            .with_accumulator_indices(C::accumulator_indices()),
+    );
+    let instances = circuit.instances();
+    let proof = create_shplonk_proof(params, pk, circuit, &instances, transcript)?;
+    Ok(Snark::new(protocol, instances, proof))
+}
+
+/// Creates a SHPLONK proof for `circuit` with the given transcript.
+#[cfg(feature = "prover")]
+pub(crate) fn create_shplonk_proof<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+    transcript: TranscriptType,
+) -> Result<Vec<u8>, Halo2WasmError> {
+    let instances = instances.iter().map(Vec::as_slice).collect_vec();
+    match transcript {
+        TranscriptType::Poseidon => {
+            let mut transcript = PoseidonTranscript::<NativeLoader, Vec<u8>>::from_spec(
+                vec![],
+                POSEIDON_SPEC.clone(),
+            );
+            create_with(params, pk, circuit, &instances, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+        #[cfg(any(feature = "evm", not(target_family = "wasm")))]
+        TranscriptType::Evm => {
+            let mut transcript =
+                EvmTranscript::<G1Affine, NativeLoader, Vec<u8>, Vec<u8>>::new(vec![]);
+            create_with(params, pk, circuit, &instances, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+        #[cfg(all(target_family = "wasm", not(feature = "evm")))]
+        TranscriptType::Evm => Err(Halo2WasmError::EvmUnsupported),
+        TranscriptType::Blake2b => {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_with(params, pk, circuit, &instances, &mut transcript)?;
+            Ok(transcript.finalize())
+        }
+    }
+}
+
+/// Verifies a SHPLONK proof read with the given transcript.
+///
+/// Returns [`Halo2WasmError::VerifyFailed`] if the proof is invalid.
+pub(crate) fn verify_shplonk_proof(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+    transcript: TranscriptType,
+) -> Result<(), Halo2WasmError> {
+    let instances = instances.iter().map(Vec::as_slice).collect_vec();
+    match transcript {
+        TranscriptType::Poseidon => {
+            let mut transcript =
+                PoseidonTranscript::<NativeLoader, &[u8]>::from_spec(proof, POSEIDON_SPEC.clone());
+            verify_with(params, vk, &instances, &mut transcript)
+        }
+        #[cfg(any(feature = "evm", not(target_family = "wasm")))]
+        TranscriptType::Evm => {
+            let mut transcript =
+                EvmTranscript::<G1Affine, NativeLoader, &[u8], Vec<u8>>::new(proof);
+            verify_with(params, vk, &instances, &mut transcript)
+        }
+        #[cfg(all(target_family = "wasm", not(feature = "evm")))]
+        TranscriptType::Evm => Err(Halo2WasmError::EvmUnsupported),
+        TranscriptType::Blake2b => {
+            let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+            verify_with(params, vk, &instances, &mut transcript)
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+fn create_with<C, E, T>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+    transcript: &mut T,
+) -> Result<(), Halo2WasmError>
+where
+    C: Circuit<Fr>,
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptWrite<G1Affine, E>,
+{
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        transcript,
+    )
+    .map_err(|e| Halo2WasmError::Synthesis(e.to_string()))
+}
+
+fn verify_with<E, T>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[Fr]],
+    transcript: &mut T,
+) -> Result<(), Halo2WasmError>
+where
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptRead<G1Affine, E>,
+{
+    let verifier_params = params.verifier_params();
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        verifier_params,
+        vk,
+        SingleStrategy::new(verifier_params),
+        &[instances],
+        transcript,
+    )
+    .map_err(|e| Halo2WasmError::VerifyFailed(e.to_string()))
+}
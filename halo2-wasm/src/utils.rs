@@ -0,0 +1,211 @@
+use std::str::FromStr;
+
+use halo2_base::{
+    halo2_proofs::halo2curves::{bn256::Fr, ff::PrimeField},
+    utils::{biguint_to_fe, fe_to_biguint, modulus},
+};
+use js_sys::Uint8Array;
+use num_bigint::BigUint;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::Halo2WasmError;
+
+/// Encoding of field elements returned by value getters and accepted by loaders.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum FieldEncoding {
+    /// Decimal string.
+    Decimal,
+    /// `0x`-prefixed big-endian hex string, zero-padded to 64 digits.
+    #[default]
+    Hex,
+    /// 32-byte little-endian `Uint8Array`, the canonical representation of `Fr`.
+    BytesLe,
+    /// 32-byte big-endian `Uint8Array`, as in EVM words.
+    BytesBe,
+}
+
+/// A field element encoded with a [`FieldEncoding`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodedFr {
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl From<EncodedFr> for JsValue {
+    fn from(value: EncodedFr) -> Self {
+        match value {
+            EncodedFr::String(s) => JsValue::from_str(&s),
+            EncodedFr::Bytes(bytes) => Uint8Array::from(bytes.as_slice()).into(),
+        }
+    }
+}
+
+/// Encodes `x` with `encoding`.
+pub fn encode_fr(x: &Fr, encoding: FieldEncoding) -> EncodedFr {
+    match encoding {
+        FieldEncoding::Decimal => EncodedFr::String(fe_to_biguint(x).to_str_radix(10)),
+        FieldEncoding::Hex => EncodedFr::String(fr_to_hex(x)),
+        FieldEncoding::BytesLe => EncodedFr::Bytes(x.to_repr().as_ref().to_vec()),
+        FieldEncoding::BytesBe => EncodedFr::Bytes(fr_to_bytes_be(x).to_vec()),
+    }
+}
+
+/// Formats `x` as a `0x`-prefixed big-endian hex string, zero-padded to 64 digits.
+///
+/// This matches the `Debug` output of `Fr` and is accepted by [`fr_from_str`].
+pub fn fr_to_hex(x: &Fr) -> String {
+    let hex: String = fr_to_bytes_be(x)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("0x{hex}")
+}
+
+/// Decodes a field element written by [`encode_fr`] with the same `encoding`.
+///
+/// Errors if the value does not match the encoding or is not less than the modulus.
+pub fn decode_fr(value: &EncodedFr, encoding: FieldEncoding) -> Result<Fr, Halo2WasmError> {
+    match (value, encoding) {
+        (EncodedFr::String(s), FieldEncoding::Decimal) => {
+            let value = BigUint::parse_bytes(s.trim().as_bytes(), 10);
+            biguint_to_fr(value, s)
+        }
+        (EncodedFr::String(s), FieldEncoding::Hex) => {
+            let s = s.trim();
+            let hex = s
+                .strip_prefix("0x")
+                .or_else(|| s.strip_prefix("0X"))
+                .ok_or_else(|| {
+                    Halo2WasmError::InvalidFieldElement(format!("`{s}` is missing the 0x prefix"))
+                })?;
+            biguint_to_fr(BigUint::parse_bytes(hex.as_bytes(), 16), s)
+        }
+        (EncodedFr::Bytes(bytes), FieldEncoding::BytesLe) => fr_from_bytes_le(bytes),
+        (EncodedFr::Bytes(bytes), FieldEncoding::BytesBe) => {
+            let mut bytes = bytes.clone();
+            bytes.reverse();
+            fr_from_bytes_le(&bytes)
+        }
+        (EncodedFr::String(_), _) => Err(Halo2WasmError::InvalidFieldElement(format!(
+            "expected a byte array for {encoding:?} encoding"
+        ))),
+        (EncodedFr::Bytes(_), _) => Err(Halo2WasmError::InvalidFieldElement(format!(
+            "expected a string for {encoding:?} encoding"
+        ))),
+    }
+}
+
+/// Decodes a field element passed from JS as a string or `Uint8Array`, see [`decode_fr`].
+pub fn decode_js_fr(value: JsValue, encoding: FieldEncoding) -> Result<Fr, Halo2WasmError> {
+    let value = match value.as_string() {
+        Some(s) => EncodedFr::String(s),
+        None => match value.dyn_into::<Uint8Array>() {
+            Ok(bytes) => EncodedFr::Bytes(bytes.to_vec()),
+            Err(_) => {
+                return Err(Halo2WasmError::InvalidFieldElement(
+                    "expected a string or Uint8Array".to_string(),
+                ))
+            }
+        },
+    };
+    decode_fr(&value, encoding)
+}
+
+pub(crate) fn fr_to_bytes_be(x: &Fr) -> [u8; 32] {
+    let mut bytes = x.to_repr();
+    bytes.reverse();
+    bytes
+}
+
+fn fr_from_bytes_le(bytes: &[u8]) -> Result<Fr, Halo2WasmError> {
+    let repr: [u8; 32] = bytes.try_into().map_err(|_| {
+        Halo2WasmError::InvalidFieldElement(format!("expected 32 bytes, found {}", bytes.len()))
+    })?;
+    Option::from(Fr::from_repr(repr)).ok_or_else(|| {
+        Halo2WasmError::InvalidFieldElement("value is not less than the field modulus".to_string())
+    })
+}
+
+fn biguint_to_fr(value: Option<BigUint>, s: &str) -> Result<Fr, Halo2WasmError> {
+    let value =
+        value.ok_or_else(|| Halo2WasmError::InvalidFieldElement(format!("cannot parse `{s}`")))?;
+    if value >= modulus::<Fr>() {
+        return Err(Halo2WasmError::InvalidFieldElement(format!(
+            "`{s}` is not less than the field modulus"
+        )));
+    }
+    Ok(biguint_to_fe(&value))
+}
+
+/// Parses a field element from either a decimal string or a `0x`-prefixed big-endian hex string.
+///
+/// Errors if the string is malformed or the value is not less than the modulus.
+pub fn fr_from_str(s: &str) -> Result<Fr, Halo2WasmError> {
+    let s = s.trim();
+    let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(s.as_bytes(), 10),
+    };
+    biguint_to_fr(value, s)
+}
+
+/// Parses an integer argument, such as a bit size, passed as a decimal string.
+pub fn parse_int<T: FromStr>(s: &str) -> Result<T, Halo2WasmError> {
+    s.trim()
+        .parse()
+        .map_err(|_| Halo2WasmError::InvalidInteger(s.to_string()))
+}
+
+/// Parses a field element from a big-endian byte array of at most 32 bytes.
+///
+/// Errors if the array is longer or the value is not less than the modulus.
+pub fn fr_from_bytes_be(bytes: &[u8]) -> Result<Fr, Halo2WasmError> {
+    if bytes.len() > 32 {
+        return Err(Halo2WasmError::InvalidFieldElement(format!(
+            "expected at most 32 bytes, found {}",
+            bytes.len()
+        )));
+    }
+    let value = BigUint::from_bytes_be(bytes);
+    biguint_to_fr(
+        Some(value.clone()),
+        &format!("0x{}", value.to_str_radix(16)),
+    )
+}
+
+/// Serializes `value` as `magic || version (u32 LE) || bincode(value)`.
+pub(crate) fn to_versioned_bytes<T: Serialize>(
+    magic: [u8; 4],
+    version: u32,
+    value: &T,
+) -> Result<Vec<u8>, Halo2WasmError> {
+    let mut bytes = magic.to_vec();
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bincode::serialize_into(&mut bytes, value)
+        .map_err(|e| Halo2WasmError::Serialize(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Deserializes bytes written by [`to_versioned_bytes`], checking `magic` and `version`.
+/// `name` describes the expected contents in errors.
+pub(crate) fn from_versioned_bytes<T: DeserializeOwned>(
+    magic: [u8; 4],
+    version: u32,
+    name: &str,
+    bytes: &[u8],
+) -> Result<T, Halo2WasmError> {
+    if bytes.len() < 8 || bytes[..4] != magic {
+        return Err(Halo2WasmError::Deserialize(format!("not a {name}")));
+    }
+    let found = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    if found != version {
+        return Err(Halo2WasmError::Deserialize(format!(
+            "unsupported {name} version {found}, expected {version}"
+        )));
+    }
+    bincode::deserialize(&bytes[8..]).map_err(|e| Halo2WasmError::Deserialize(e.to_string()))
+}
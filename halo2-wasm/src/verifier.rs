@@ -0,0 +1,180 @@
+use std::io::BufReader;
+
+use halo2_base::gates::circuit::{builder::BaseCircuitBuilder, BaseCircuitParams};
+use halo2_base::halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::VerifyingKey,
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+    SerdeFormat,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    artifact::CircuitArtifact,
+    snark::load_snark_artifact,
+    transcript::{verify_shplonk_proof, TranscriptType},
+    utils::fr_from_str,
+    Halo2WasmError,
+};
+
+/// Verifies proofs for a single circuit given only its verifying key and verifier params.
+///
+/// Unlike [`crate::Halo2Wasm`], this never builds the circuit and only needs the first G1 point
+/// of the SRS (see [`verifier_params`]) rather than the full params file.
+#[wasm_bindgen]
+pub struct Halo2Verifier {
+    params: ParamsKZG<Bn256>,
+    vk: VerifyingKey<G1Affine>,
+    transcript: TranscriptType,
+}
+
+#[wasm_bindgen]
+impl Halo2Verifier {
+    /// * `circuit_params`: bincode serialized `BaseCircuitParams` the vk was generated with
+    /// * `vk`: verifying key bytes, as returned by `Halo2Wasm.getVk`
+    /// * `params`: verifier params, as returned by `Halo2Wasm.getVerifierParams`, or full params
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        circuit_params: &[u8],
+        vk: &[u8],
+        params: &[u8],
+    ) -> Result<Halo2Verifier, Halo2WasmError> {
+        let circuit_params: BaseCircuitParams = bincode::deserialize(circuit_params)
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        Self::from_keys(circuit_params, vk, params)
+    }
+
+    /// Creates a verifier from an artifact created by `Halo2Wasm.exportCircuitArtifact`,
+    /// so the verifying key is always read with the configuration it was generated with.
+    #[wasm_bindgen(js_name = fromArtifact)]
+    pub fn from_artifact(artifact: &[u8], params: &[u8]) -> Result<Halo2Verifier, Halo2WasmError> {
+        let artifact = CircuitArtifact::from_bytes(artifact)?;
+        Self::from_keys(artifact.circuit_params, &artifact.vk, params)
+    }
+
+    /// Verifies `proof` against public inputs given as one array of field element strings
+    /// (decimal or `0x`-prefixed hex) per instance column.
+    ///
+    /// Returns `false` if the proof is invalid.
+    pub fn verify(&self, proof: &[u8], instances: JsValue) -> Result<bool, Halo2WasmError> {
+        let instances: Vec<Vec<String>> = serde_wasm_bindgen::from_value(instances)
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        let instances = instances
+            .iter()
+            .map(|col| col.iter().map(|x| fr_from_str(x)).collect())
+            .collect::<Result<Vec<Vec<Fr>>, _>>()?;
+        self.verify_with_instances(proof, &instances)
+    }
+
+    /// Sets the transcript proofs passed to `verify` were created with, defaults to Poseidon.
+    #[wasm_bindgen(js_name = setTranscript)]
+    pub fn set_transcript(&mut self, transcript: TranscriptType) {
+        self.transcript = transcript;
+    }
+
+    /// Verifies a snark created by `Halo2Wasm.proveSnark` against its own instances, using the
+    /// transcript recorded in it.
+    ///
+    /// Returns `false` if the proof is invalid.
+    #[wasm_bindgen(js_name = verifySnark)]
+    pub fn verify_snark(&self, snark: &[u8]) -> Result<bool, Halo2WasmError> {
+        let artifact = load_snark_artifact(snark)?;
+        self.verify_with_transcript(
+            &artifact.snark.proof,
+            &artifact.snark.instances,
+            artifact.transcript,
+        )
+    }
+}
+
+impl Halo2Verifier {
+    fn from_keys(
+        circuit_params: BaseCircuitParams,
+        vk: &[u8],
+        params: &[u8],
+    ) -> Result<Self, Halo2WasmError> {
+        let k = circuit_params.k as u32;
+        let vk = read_vk(vk, circuit_params)?;
+        let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params))
+            .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))?;
+        // The SHPLONK verifier only reads `g[0]`, `g2` and `s_g2`, but needs `n` to match the circuit.
+        let params = params.from_parts(
+            k,
+            vec![params.get_g()[0]],
+            Some(vec![]),
+            params.g2(),
+            params.s_g2(),
+        );
+        Ok(Self {
+            params,
+            vk,
+            transcript: TranscriptType::default(),
+        })
+    }
+
+    /// Verifies `proof` against `instances`, one vector per instance column.
+    ///
+    /// Returns `Ok(false)` if the proof is invalid and `Err` if verification could not be run.
+    pub fn verify_with_instances(
+        &self,
+        proof: &[u8],
+        instances: &[Vec<Fr>],
+    ) -> Result<bool, Halo2WasmError> {
+        self.verify_with_transcript(proof, instances, self.transcript)
+    }
+
+    fn verify_with_transcript(
+        &self,
+        proof: &[u8],
+        instances: &[Vec<Fr>],
+        transcript: TranscriptType,
+    ) -> Result<bool, Halo2WasmError> {
+        match verify_shplonk(&self.params, &self.vk, proof, instances, transcript) {
+            Ok(()) => Ok(true),
+            Err(Halo2WasmError::VerifyFailed(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Returns the minimal params needed to verify SHPLONK proofs: a degree 0 `ParamsKZG`
+/// carrying `g[0]`, `g2` and `s_g2` of `params`.
+pub fn verifier_params(params: &ParamsKZG<Bn256>) -> ParamsKZG<Bn256> {
+    let g = params.get_g()[0];
+    params.from_parts(0, vec![g], Some(vec![g]), params.g2(), params.s_g2())
+}
+
+pub(crate) fn read_vk(
+    vk: &[u8],
+    circuit_params: BaseCircuitParams,
+) -> Result<VerifyingKey<G1Affine>, Halo2WasmError> {
+    VerifyingKey::<G1Affine>::read::<BufReader<&[u8]>, BaseCircuitBuilder<Fr>>(
+        &mut BufReader::new(vk),
+        SerdeFormat::RawBytesUnchecked,
+        circuit_params,
+    )
+    .map_err(|e| Halo2WasmError::Deserialize(e.to_string()))
+}
+
+/// Verifies a SHPLONK proof with the given transcript.
+///
+/// Returns [`Halo2WasmError::VerifyFailed`] if the proof is invalid.
+pub(crate) fn verify_shplonk(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+    transcript: TranscriptType,
+) -> Result<(), Halo2WasmError> {
+    let expected = vk.cs().num_instance_columns();
+    if instances.len() != expected {
+        return Err(Halo2WasmError::InstanceColumnMismatch {
+            expected,
+            found: instances.len(),
+        });
+    }
+    verify_shplonk_proof(params, vk, proof, instances, transcript)
+}